@@ -0,0 +1,56 @@
+//! Probe whether the compiler supports the unstable `error_generic_member_access` API, when the
+//! `error-generic-member-access` feature is enabled, so that the feature is ignored on a stable
+//! toolchain, e.g. with `--all-features`, instead of failing the build.
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+const PROBE: &str = r#"
+#![feature(error_generic_member_access)]
+
+pub fn probe(error: &(dyn std::error::Error + 'static)) -> bool {
+    std::error::request_ref::<std::backtrace::Backtrace>(error).is_some()
+}
+"#;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rustc-check-cfg=cfg(error_generic_member_access)");
+
+    if env::var_os("CARGO_FEATURE_ERROR_GENERIC_MEMBER_ACCESS").is_none() {
+        return;
+    }
+    if probe() {
+        println!("cargo:rustc-cfg=error_generic_member_access");
+    } else {
+        println!(
+            "cargo:warning=the error-generic-member-access feature requires a nightly toolchain, \
+             and is ignored"
+        );
+    }
+}
+
+/// Compile the probe with the compiler building the crate.
+fn probe() -> bool {
+    let out_dir = match env::var_os("OUT_DIR") {
+        Some(out_dir) => PathBuf::from(out_dir),
+        None => return false,
+    };
+    let probe = out_dir.join("probe.rs");
+    if fs::write(&probe, PROBE).is_err() {
+        return false;
+    }
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    Command::new(rustc)
+        .arg("--edition=2018")
+        .arg("--crate-name=honeybadger_probe")
+        .arg("--crate-type=lib")
+        .arg("--emit=metadata")
+        .arg("--out-dir")
+        .arg(&out_dir)
+        .arg(&probe)
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
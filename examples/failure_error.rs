@@ -7,7 +7,7 @@ extern crate failure;
 #[fail(display = "Failure error")]
 struct MyCustomError;
 
-use honeybadger::{ConfigBuilder, Honeybadger};
+use honeybadger::{notice, ConfigBuilder, Honeybadger};
 use tokio::runtime::Runtime;
 
 async fn run() -> std::result::Result<(), honeybadger::errors::Error> {
@@ -17,7 +17,7 @@ async fn run() -> std::result::Result<(), honeybadger::errors::Error> {
 
     match make_error() {
         Ok(_) => Ok(()),
-        Err(e) => Ok(honeybadger.notify(&e, None).await?)
+        Err(e) => Ok(honeybadger.notify(notice::Error::from(&e), None).await?),
     }
 }
 
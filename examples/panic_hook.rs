@@ -0,0 +1,15 @@
+//! Example that installs the panic hook, and reports a panic to honeybadger
+
+use honeybadger::{ConfigBuilder, Honeybadger};
+
+fn main() {
+    let api_token = "ffffff";
+    let config = ConfigBuilder::new(api_token).build();
+    honeybadger::panic::install(Honeybadger::new(config).unwrap());
+
+    make_panic();
+}
+
+fn make_panic() {
+    panic!("Panic error");
+}
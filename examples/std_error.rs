@@ -12,10 +12,7 @@ async fn run() -> std::result::Result<(), honeybadger::errors::Error> {
 
     match make_error() {
         Ok(_) => Ok(()),
-        Err(e) => {
-            let boxed: Box<dyn std::error::Error> = e.into();
-            Ok(honeybadger.notify(boxed, None).await?)
-        }
+        Err(e) => Ok(honeybadger.notify(e, None).await?),
     }
 }
 
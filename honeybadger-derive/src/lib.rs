@@ -0,0 +1,352 @@
+//! Derive macro for the `IntoNotice` trait of the [honeybadger](https://docs.rs/honeybadger)
+//! crate
+//!
+//! The macro is re-exported by the `honeybadger` crate with the `derive` feature. It implements
+//! `IntoNotice` for an enum or a struct, and the conversion into a `notice::Error`, using the
+//! `#[honeybadger(...)]` attributes:
+//!
+//!  - `class = "..."` - the class of the notice, which defaults to the path of the type, followed
+//!    by the name of the variant for an enum.
+//!
+//!  - `fingerprint = "..."` - the fingerprint grouping the notices in Honeybadger.
+//!
+//!  - `tags("...", ...)` - the tags of the notice. Tags of the type are added to the tags of each
+//!    variant.
+//!
+//!  - `ignore` - the error is expected, and not reported.
+//!
+//!  - `source` - the causes of the notice are the chain of `std::error::Error::source`. The type
+//!    must implement `std::error::Error`, and be `'static`.
+//!
+//!  - `context` or `context = "..."` on a field - the field is copied into the context of the
+//!    notice, under its name or the given key. The field must implement `serde::Serialize`.
+//!
+//! The attributes of the type apply to every variant, unless overridden by the variant. The
+//! message of the notice is the display text of the error.
+//!
+//! A type deriving `HoneybadgerNotice` implements `Display`, and may implement
+//! `std::error::Error` as well, for example with `#[derive(thiserror::Error)]`, in which case
+//! `#[honeybadger(source)]` reports its sources.
+//!
+//! # Example
+//!
+//! ```rust
+//! use honeybadger::{HoneybadgerNotice, IntoNotice};
+//!
+//! #[derive(Debug, HoneybadgerNotice)]
+//! #[honeybadger(tags("payments"))]
+//! enum PaymentError {
+//!     #[honeybadger(class = "PaymentDeclined", tags("retryable"))]
+//!     Declined {
+//!         #[honeybadger(context)]
+//!         order_id: u64,
+//!     },
+//!     #[honeybadger(ignore)]
+//!     Cancelled,
+//! }
+//!
+//! impl std::fmt::Display for PaymentError {
+//!     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+//!         write!(f, "payment failed")
+//!     }
+//! }
+//!
+//! let error = PaymentError::Declined { order_id: 42 };
+//! assert_eq!(Some(&serde_json::json!(42)), error.context().get("order_id"));
+//!
+//! let notice: honeybadger::notice::Error = error.into();
+//! assert_eq!("PaymentDeclined", notice.class);
+//! assert_eq!(vec!["payments", "retryable"], notice.tags);
+//! assert!(PaymentError::Cancelled.is_ignored());
+//! ```
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::punctuated::Punctuated;
+use syn::{
+    parenthesized, parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, LitStr, Member,
+    Result, Token,
+};
+
+/// Derive `IntoNotice`, and the conversion into a `notice::Error`, see the
+/// [crate documentation](index.html).
+#[proc_macro_derive(HoneybadgerNotice, attributes(honeybadger))]
+pub fn derive_honeybadger_notice(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Notice metadata of a type or a variant, parsed from its `#[honeybadger(...)]` attributes.
+#[derive(Default)]
+struct Metadata {
+    class: Option<LitStr>,
+    fingerprint: Option<LitStr>,
+    tags: Vec<LitStr>,
+    ignore: bool,
+    source: bool,
+}
+
+impl Metadata {
+    fn parse(attrs: &[Attribute]) -> Result<Metadata> {
+        let mut metadata = Metadata::default();
+        for attr in attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("honeybadger"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("class") {
+                    metadata.class = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("fingerprint") {
+                    metadata.fingerprint = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("tags") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let tags = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+                    metadata.tags.extend(tags);
+                } else if meta.path.is_ident("ignore") {
+                    metadata.ignore = true;
+                } else if meta.path.is_ident("source") {
+                    metadata.source = true;
+                } else {
+                    return Err(meta.error("unsupported honeybadger attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(metadata)
+    }
+
+    /// Metadata of a variant, completed by the metadata of its type.
+    fn inherit(self, parent: &Metadata) -> Metadata {
+        Metadata {
+            class: self.class.or_else(|| parent.class.clone()),
+            fingerprint: self.fingerprint.or_else(|| parent.fingerprint.clone()),
+            tags: parent.tags.iter().cloned().chain(self.tags).collect(),
+            ignore: self.ignore || parent.ignore,
+            source: self.source || parent.source,
+        }
+    }
+}
+
+/// A variant of an enum, or the struct itself, with the fields copied into the context.
+struct Variant {
+    path: TokenStream2,
+    class: TokenStream2,
+    metadata: Metadata,
+    context: Vec<(Member, LitStr)>,
+}
+
+impl Variant {
+    fn new(
+        path: TokenStream2,
+        name: String,
+        attrs: &[Attribute],
+        fields: &Fields,
+        parent: &Metadata,
+    ) -> Result<Variant> {
+        let metadata = Metadata::parse(attrs)?.inherit(parent);
+        let class = match metadata.class {
+            Some(ref class) => quote!(#class),
+            None => quote!(::std::concat!(::std::module_path!(), "::", #name)),
+        };
+        let mut context = Vec::new();
+        for (index, field) in fields.iter().enumerate() {
+            let member = match field.ident {
+                Some(ref ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(index.into()),
+            };
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("honeybadger"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if !meta.path.is_ident("context") {
+                        return Err(meta.error("unsupported honeybadger field attribute"));
+                    }
+                    let key = if meta.input.peek(Token![=]) {
+                        meta.value()?.parse()?
+                    } else {
+                        let key = match member {
+                            Member::Named(ref ident) => ident.to_string(),
+                            Member::Unnamed(ref index) => index.index.to_string(),
+                        };
+                        LitStr::new(&key, proc_macro2::Span::call_site())
+                    };
+                    context.push((member.clone(), key));
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(Variant {
+            path,
+            class,
+            metadata,
+            context,
+        })
+    }
+
+    /// Pattern matching the variant, without binding its fields.
+    fn pattern(&self) -> TokenStream2 {
+        let path = &self.path;
+        quote!(#path { .. })
+    }
+
+    /// Pattern matching the variant, and binding the fields copied into the context.
+    fn context_pattern(&self) -> (TokenStream2, Vec<proc_macro2::Ident>) {
+        let path = &self.path;
+        let members = self.context.iter().map(|(member, _)| member);
+        let bindings: Vec<_> = (0..self.context.len())
+            .map(|index| format_ident!("__honeybadger_context_{}", index))
+            .collect();
+        (quote!(#path { #(#members: #bindings,)* .. }), bindings)
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let parent = Metadata::parse(&input.attrs)?;
+    let variants = match input.data {
+        Data::Enum(ref data) if !data.variants.is_empty() => data
+            .variants
+            .iter()
+            .map(|variant| {
+                let ident = &variant.ident;
+                Variant::new(
+                    quote!(Self::#ident),
+                    format!("{}::{}", name, ident),
+                    &variant.attrs,
+                    &variant.fields,
+                    &parent,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?,
+        Data::Enum(_) => {
+            return Err(Error::new_spanned(
+                input,
+                "HoneybadgerNotice cannot be derived for enums without variants",
+            ))
+        }
+        Data::Struct(ref data) => vec![Variant::new(
+            quote!(Self),
+            name.to_string(),
+            &[],
+            &data.fields,
+            &parent,
+        )?],
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                input,
+                "HoneybadgerNotice cannot be derived for unions",
+            ))
+        }
+    };
+
+    let patterns: Vec<_> = variants.iter().map(Variant::pattern).collect();
+    let classes = variants.iter().map(|variant| &variant.class);
+    let fingerprints = variants
+        .iter()
+        .map(|variant| match variant.metadata.fingerprint {
+            Some(ref fingerprint) => quote!(::std::option::Option::Some(
+                ::std::string::String::from(#fingerprint)
+            )),
+            None => quote!(::std::option::Option::None),
+        });
+    let tags = variants.iter().map(|variant| {
+        let tags = &variant.metadata.tags;
+        quote!(::std::vec![#(::std::string::String::from(#tags)),*])
+    });
+    let ignored = variants.iter().map(|variant| variant.metadata.ignore);
+    let causes = if variants.iter().any(|variant| variant.metadata.source) {
+        let causes = variants.iter().map(|variant| {
+            if variant.metadata.source {
+                quote!(::honeybadger::__private::causes(self))
+            } else {
+                quote!(::std::option::Option::None)
+            }
+        });
+        quote! {
+            fn causes(
+                &self,
+            ) -> ::std::option::Option<::std::vec::Vec<::honeybadger::notice::Error>> {
+                match self {
+                    #(#patterns => #causes,)*
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+    let contexts = variants.iter().map(|variant| {
+        let (pattern, bindings) = variant.context_pattern();
+        let keys = variant.context.iter().map(|(_, key)| key);
+        quote! {
+            #pattern => ::std::vec![#((
+                ::std::string::String::from(#keys),
+                ::honeybadger::__private::serde_json::to_value(#bindings).unwrap_or_default(),
+            )),*]
+            .into_iter()
+            .collect(),
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::honeybadger::IntoNotice for #name #ty_generics #where_clause {
+            fn class(&self) -> ::std::string::String {
+                match self {
+                    #(#patterns => ::std::string::String::from(#classes),)*
+                }
+            }
+
+            fn message(&self) -> ::std::option::Option<::std::string::String> {
+                ::std::option::Option::Some(::std::string::ToString::to_string(self))
+            }
+
+            #causes
+
+            fn fingerprint(&self) -> ::std::option::Option<::std::string::String> {
+                match self {
+                    #(#patterns => #fingerprints,)*
+                }
+            }
+
+            fn tags(&self) -> ::std::vec::Vec<::std::string::String> {
+                match self {
+                    #(#patterns => #tags,)*
+                }
+            }
+
+            fn context(
+                &self,
+            ) -> ::std::collections::HashMap<
+                ::std::string::String,
+                ::honeybadger::__private::serde_json::Value,
+            > {
+                match self {
+                    #(#contexts)*
+                }
+            }
+
+            fn is_ignored(&self) -> bool {
+                match self {
+                    #(#patterns => #ignored,)*
+                }
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::std::convert::From<#name #ty_generics>
+            for ::honeybadger::notice::Error #where_clause
+        {
+            fn from(error: #name #ty_generics) -> Self {
+                ::honeybadger::IntoNotice::into_notice_error(error)
+            }
+        }
+    })
+}
@@ -0,0 +1,128 @@
+use honeybadger::{notice, HoneybadgerNotice, IntoNotice};
+use serde_json::json;
+use std::fmt;
+
+#[derive(Debug, HoneybadgerNotice)]
+#[honeybadger(tags("db"))]
+enum StoreError {
+    #[honeybadger(
+        class = "StoreTimeout",
+        fingerprint = "store-timeout",
+        tags("retryable")
+    )]
+    Timeout(#[honeybadger(context = "timeout_ms")] u64),
+    Conflict {
+        #[honeybadger(context)]
+        table: String,
+        #[honeybadger(context)]
+        id: u32,
+        attempt: u8,
+    },
+    #[honeybadger(ignore)]
+    NotFound,
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Timeout(ms) => write!(f, "timed out after {}ms", ms),
+            StoreError::Conflict { table, attempt, .. } => {
+                write!(f, "conflict in {} on attempt {}", table, attempt)
+            }
+            StoreError::NotFound => write!(f, "not found"),
+        }
+    }
+}
+
+#[derive(Debug, HoneybadgerNotice)]
+#[honeybadger(fingerprint = "config")]
+struct ConfigError<T: fmt::Debug + serde::Serialize> {
+    #[honeybadger(context)]
+    value: T,
+}
+
+impl<T: fmt::Debug + serde::Serialize> fmt::Display for ConfigError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid value {:?}", self.value)
+    }
+}
+
+#[derive(Debug, thiserror::Error, HoneybadgerNotice)]
+#[honeybadger(tags("sync"), source)]
+enum SyncError {
+    #[error("unable to read {path}")]
+    #[honeybadger(class = "SyncRead")]
+    Read {
+        #[honeybadger(context)]
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Config(#[from] ConfigError<u16>),
+}
+
+impl<T: fmt::Debug + serde::Serialize> std::error::Error for ConfigError<T> {}
+
+#[test]
+fn test_derive_enum() {
+    let error = StoreError::Timeout(250);
+    assert_eq!(Some(&json!(250)), error.context().get("timeout_ms"));
+    assert!(!error.is_ignored());
+
+    let notice: notice::Error = error.into();
+    assert_eq!("StoreTimeout", notice.class);
+    assert_eq!(Some("timed out after 250ms".to_owned()), notice.message);
+    assert_eq!(Some("store-timeout".to_owned()), notice.fingerprint);
+    assert_eq!(vec!["db", "retryable"], notice.tags);
+
+    let error = StoreError::Conflict {
+        table: "users".to_owned(),
+        id: 7,
+        attempt: 2,
+    };
+    let context = error.context();
+    assert_eq!(2, context.len());
+    assert_eq!(Some(&json!("users")), context.get("table"));
+    assert_eq!(Some(&json!(7)), context.get("id"));
+    assert_eq!("derive::StoreError::Conflict", error.class());
+    assert_eq!(None, error.fingerprint());
+    assert_eq!(vec!["db"], error.tags());
+
+    assert!(StoreError::NotFound.is_ignored());
+    assert!(StoreError::NotFound.context().is_empty());
+}
+
+#[test]
+fn test_derive_struct() {
+    let error = ConfigError { value: vec![1, 2] };
+
+    assert_eq!("derive::ConfigError", error.class());
+    assert_eq!(Some(&json!([1, 2])), error.context().get("value"));
+    assert_eq!(Some("config".to_owned()), error.fingerprint());
+    assert!(error.causes().is_none());
+}
+
+#[test]
+fn test_derive_std_error() {
+    let error = SyncError::Read {
+        path: "/var/sync".to_owned(),
+        source: std::io::Error::new(std::io::ErrorKind::NotFound, "missing"),
+    };
+    assert!(std::error::Error::source(&error).is_some());
+    assert_eq!(Some(&json!("/var/sync")), error.context().get("path"));
+
+    let notice: notice::Error = error.into();
+    assert_eq!("SyncRead", notice.class);
+    assert_eq!(Some("unable to read /var/sync".to_owned()), notice.message);
+    assert_eq!(vec!["sync"], notice.tags);
+    let causes = notice.causes.expect("causes");
+    assert_eq!(1, causes.len());
+    assert_eq!("std::io::error::Error", causes[0].class);
+    assert_eq!(Some("missing".to_owned()), causes[0].message);
+
+    let error = SyncError::from(ConfigError { value: 8080 });
+    assert_eq!("derive::SyncError::Config", error.class());
+    assert_eq!(Some("invalid value 8080".to_owned()), error.message());
+    assert!(error.causes().is_none());
+}
@@ -0,0 +1,396 @@
+//! Fake Honeybadger API server for local development and CI
+//!
+//! Implements the notices, check-in and deploy endpoints of the Honeybadger API, validates the
+//! `X-API-Key` header and the shape of the payloads, and writes every accepted notice as a JSON
+//! line to the output file, or to stdout.
+//!
+//! Failures are injected by sending a control request, before exercising the client:
+//!
+//! ```text
+//! # reply to the next 3 requests with a '429 Too Many Requests' and a Retry-After header
+//! curl -X PUT 'http://127.0.0.1:4567/_fake/responses?status=429&count=3&retry_after=1'
+//! # reply normally again
+//! curl -X DELETE 'http://127.0.0.1:4567/_fake/responses'
+//! ```
+use std::convert::Infallible;
+use std::env;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::SocketAddr;
+use std::process;
+use std::sync::{Arc, Mutex};
+
+use http::{Method, Request, Response, StatusCode};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Server};
+use serde_json::{json, Value};
+
+use honeybadger::notice::Notice;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:4567";
+const NOTICES_PATH: &str = "/v1/notices";
+const DEPLOYS_PATH: &str = "/v1/deploys";
+const CHECK_IN_PREFIX: &str = "/v1/check_in/";
+const RESPONSES_PATH: &str = "/_fake/responses";
+
+const USAGE: &str = "Usage: honeybadger-fake-server [OPTIONS]
+
+Options:
+    --addr <ADDR>        address to listen on (default: 127.0.0.1:4567)
+    --api-key <KEY>      only accept this API key (default: accept any non-empty key)
+    --output <FILE>      append received notices to FILE as JSON lines (default: stdout)
+    -h, --help           print this help";
+
+/// Response injected in place of the regular responses of the API endpoints.
+#[derive(Debug)]
+struct Injected {
+    status: StatusCode,
+    remaining: u32,
+    retry_after: Option<u64>,
+}
+
+struct State {
+    api_key: Option<String>,
+    output: Mutex<Box<dyn Write + Send>>,
+    injected: Mutex<Option<Injected>>,
+}
+
+struct Options {
+    addr: SocketAddr,
+    api_key: Option<String>,
+    output: Option<String>,
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let output: Box<dyn Write + Send> = match options.output {
+        Some(ref path) => match OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                eprintln!("Unable to open {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+        None => Box::new(io::stdout()),
+    };
+    let state = Arc::new(State {
+        api_key: options.api_key,
+        output: Mutex::new(output),
+        injected: Mutex::new(None),
+    });
+
+    let make_service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |request| handle(state.clone(), request))) }
+    });
+
+    let addr = options.addr;
+    let mut rt = tokio::runtime::Runtime::new().expect("Unable to start the tokio runtime");
+    let result = rt.block_on(async {
+        let server = Server::try_bind(&addr)?.serve(make_service);
+        eprintln!("Fake Honeybadger server listening on http://{}", addr);
+        server.await
+    });
+    if let Err(e) = result {
+        eprintln!("Fake Honeybadger server failed: {}", e);
+        process::exit(1);
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+    let mut options = Options {
+        addr: DEFAULT_ADDR.parse().unwrap(),
+        api_key: None,
+        output: None,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--addr" => {
+                let addr = value()?;
+                options.addr = addr
+                    .parse()
+                    .map_err(|_| format!("Invalid address: {}", addr))?;
+            }
+            "--api-key" => options.api_key = Some(value()?),
+            "--output" => options.output = Some(value()?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => return Err(format!("Unknown argument: {}", arg)),
+        }
+    }
+    Ok(options)
+}
+
+async fn handle(state: Arc<State>, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let path = request.uri().path().to_owned();
+    let response = match (request.method(), path.as_str()) {
+        (&Method::PUT, RESPONSES_PATH) => inject(&state, &request),
+        (&Method::DELETE, RESPONSES_PATH) => {
+            *state.injected.lock().unwrap() = None;
+            respond(StatusCode::NO_CONTENT, None)
+        }
+        (_, RESPONSES_PATH) => respond(StatusCode::METHOD_NOT_ALLOWED, None),
+        _ => match take_injected(&state) {
+            Some(response) => response,
+            None => route(&state, request, &path).await,
+        },
+    };
+    eprintln!("{} {}", path, response.status());
+    Ok(response)
+}
+
+async fn route(state: &State, request: Request<Body>, path: &str) -> Response<Body> {
+    match (request.method(), path) {
+        (&Method::POST, NOTICES_PATH) | (&Method::POST, DEPLOYS_PATH)
+            if !authorized(state, &request) =>
+        {
+            error(StatusCode::UNAUTHORIZED, "Invalid API key")
+        }
+        (&Method::POST, NOTICES_PATH) => notice(state, body(request).await),
+        (&Method::POST, DEPLOYS_PATH) => deploy(body(request).await),
+        (&Method::GET, _) | (&Method::POST, _) if path.starts_with(CHECK_IN_PREFIX) => {
+            let id = &path[CHECK_IN_PREFIX.len()..];
+            if id.is_empty() || id.contains('/') {
+                respond(StatusCode::NOT_FOUND, None)
+            } else {
+                eprintln!("Check-in {}", id);
+                respond(StatusCode::OK, None)
+            }
+        }
+        (_, NOTICES_PATH) | (_, DEPLOYS_PATH) => respond(StatusCode::METHOD_NOT_ALLOWED, None),
+        _ => respond(StatusCode::NOT_FOUND, None),
+    }
+}
+
+/// Configure the injected response, from the `status`, `count` and `retry_after` query
+/// parameters. The response is injected once by default.
+fn inject(state: &State, request: &Request<Body>) -> Response<Body> {
+    let mut injected = Injected {
+        status: StatusCode::INTERNAL_SERVER_ERROR,
+        remaining: 1,
+        retry_after: None,
+    };
+    let query = request.uri().query().unwrap_or("");
+    for (key, value) in url::form_urlencoded::parse(query.as_bytes()) {
+        let valid = match key.as_ref() {
+            "status" => value
+                .parse()
+                .ok()
+                .and_then(|status| StatusCode::from_u16(status).ok())
+                .map(|status| injected.status = status)
+                .is_some(),
+            "count" => value
+                .parse()
+                .map(|count| injected.remaining = count)
+                .is_ok(),
+            "retry_after" => value
+                .parse()
+                .map(|seconds| injected.retry_after = Some(seconds))
+                .is_ok(),
+            _ => false,
+        };
+        if !valid {
+            return error(
+                StatusCode::BAD_REQUEST,
+                &format!("Invalid parameter {}={}", key, value),
+            );
+        }
+    }
+
+    eprintln!("Injecting {:?}", injected);
+    *state.injected.lock().unwrap() = Some(injected);
+    respond(StatusCode::NO_CONTENT, None)
+}
+
+fn take_injected(state: &State) -> Option<Response<Body>> {
+    let mut injected = state.injected.lock().unwrap();
+    let (status, retry_after) = match *injected {
+        Some(ref mut current) if current.remaining > 0 => {
+            current.remaining -= 1;
+            (current.status, current.retry_after)
+        }
+        _ => return None,
+    };
+    if injected
+        .as_ref()
+        .is_some_and(|current| current.remaining == 0)
+    {
+        *injected = None;
+    }
+
+    let mut response = error(status, "Injected by the fake server");
+    if let Some(seconds) = retry_after {
+        response
+            .headers_mut()
+            .insert(http::header::RETRY_AFTER, seconds.into());
+    }
+    Some(response)
+}
+
+fn authorized(state: &State, request: &Request<Body>) -> bool {
+    let api_key = request
+        .headers()
+        .get("X-API-Key")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("");
+    match state.api_key {
+        Some(ref expected) => api_key == expected,
+        None => !api_key.is_empty(),
+    }
+}
+
+async fn body(request: Request<Body>) -> Vec<u8> {
+    hyper::body::to_bytes(request.into_body())
+        .await
+        .map(|bytes| bytes.to_vec())
+        .unwrap_or_default()
+}
+
+fn notice(state: &State, body: Vec<u8>) -> Response<Body> {
+    let notice: Notice = match serde_json::from_slice(&body) {
+        Ok(notice) => notice,
+        Err(e) => return error(StatusCode::UNPROCESSABLE_ENTITY, &e.to_string()),
+    };
+    if notice.error.class.is_empty() {
+        return error(StatusCode::UNPROCESSABLE_ENTITY, "Missing error class");
+    }
+
+    let line = match serde_json::to_string(&notice) {
+        Ok(line) => line,
+        Err(e) => return error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string()),
+    };
+    let mut output = state.output.lock().unwrap();
+    if let Err(e) = writeln!(output, "{}", line).and_then(|_| output.flush()) {
+        return error(StatusCode::INTERNAL_SERVER_ERROR, &e.to_string());
+    }
+
+    let id = format!("{:x}-{:x}", notice.server.time, notice.server.pid);
+    respond(StatusCode::CREATED, Some(json!({ "id": id })))
+}
+
+fn deploy(body: Vec<u8>) -> Response<Body> {
+    let deploy: Value = match serde_json::from_slice(&body) {
+        Ok(deploy) => deploy,
+        Err(e) => return error(StatusCode::UNPROCESSABLE_ENTITY, &e.to_string()),
+    };
+    match deploy
+        .pointer("/deploy/environment")
+        .and_then(Value::as_str)
+    {
+        Some(environment) => {
+            eprintln!("Deploy to {}", environment);
+            respond(StatusCode::CREATED, Some(json!({ "status": "OK" })))
+        }
+        None => error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Missing deploy environment",
+        ),
+    }
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    respond(status, Some(json!({ "error": message })))
+}
+
+fn respond(status: StatusCode, body: Option<Value>) -> Response<Body> {
+    let body = match body {
+        Some(value) => Body::from(value.to_string()),
+        None => Body::empty(),
+    };
+    let mut response = Response::new(body);
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use tokio::runtime::Runtime;
+
+    fn state(api_key: Option<&str>) -> Arc<State> {
+        Arc::new(State {
+            api_key: api_key.map(|key| key.to_owned()),
+            output: Mutex::new(Box::new(io::sink())),
+            injected: Mutex::new(None),
+        })
+    }
+
+    fn request(method: &str, uri: &str, api_key: &str, body: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("X-API-Key", api_key)
+            .body(Body::from(body.to_owned()))
+            .unwrap()
+    }
+
+    fn status(state: &Arc<State>, request: Request<Body>) -> StatusCode {
+        let mut rt = Runtime::new().unwrap();
+        rt.block_on(handle(state.clone(), request))
+            .unwrap()
+            .status()
+    }
+
+    #[test]
+    fn test_notices() {
+        let state = state(Some("dummy-api-key"));
+        let notice = r#"{"api_key":"dummy-api-key",
+            "notifier":{"name":"honeybadger","url":"https://example.com","version":"0.0.0"},
+            "error":{"class":"Error","message":null,"causes":null,"backtrace":null},
+            "request":{"context":null,"cgi_data":{}},
+            "server":{"project_root":"","environment_name":"","hostname":"","time":0,"pid":0}}"#;
+
+        let created = request("POST", NOTICES_PATH, "dummy-api-key", notice);
+        assert_eq!(StatusCode::CREATED, status(&state, created));
+        let unauthorized = request("POST", NOTICES_PATH, "other-api-key", notice);
+        assert_eq!(StatusCode::UNAUTHORIZED, status(&state, unauthorized));
+        let invalid = request("POST", NOTICES_PATH, "dummy-api-key", "{}");
+        assert_eq!(StatusCode::UNPROCESSABLE_ENTITY, status(&state, invalid));
+    }
+
+    #[test]
+    fn test_deploys_and_check_ins() {
+        let state = state(None);
+
+        let deploy = r#"{"deploy":{"environment":"production","revision":"abc"}}"#;
+        let created = request("POST", DEPLOYS_PATH, "dummy-api-key", deploy);
+        assert_eq!(StatusCode::CREATED, status(&state, created));
+        let check_in = request("GET", "/v1/check_in/abcd", "", "");
+        assert_eq!(StatusCode::OK, status(&state, check_in));
+    }
+
+    #[test]
+    fn test_injected_responses() {
+        let state = state(None);
+
+        let inject = request("PUT", "/_fake/responses?status=429&count=2", "", "");
+        assert_eq!(StatusCode::NO_CONTENT, status(&state, inject));
+        for _ in 0..2 {
+            let check_in = request("GET", "/v1/check_in/abcd", "", "");
+            assert_eq!(StatusCode::TOO_MANY_REQUESTS, status(&state, check_in));
+        }
+        let check_in = request("GET", "/v1/check_in/abcd", "", "");
+        assert_eq!(StatusCode::OK, status(&state, check_in));
+
+        let invalid = request("PUT", "/_fake/responses?status=abc", "", "");
+        assert_eq!(StatusCode::BAD_REQUEST, status(&state, invalid));
+    }
+}
@@ -0,0 +1,235 @@
+//! Blocking Honeybadger client, for applications without a Tokio runtime
+//!
+//! The blocking client owns a dedicated runtime, whose number of worker threads is set by
+//! [`ConfigBuilder::with_threads`](../struct.ConfigBuilder.html#method.with_threads). Its methods
+//! may be called from any thread, including from within another asynchronous runtime.
+//!
+//! # Example
+//!
+//! ```rust, no_run
+//! # use honeybadger::ConfigBuilder;
+//! # let api_token = "ffffff";
+//! let config = ConfigBuilder::new(api_token).build();
+//! let honeybadger = honeybadger::blocking::Honeybadger::new(config).unwrap();
+//!
+//! let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+//! honeybadger.notify(error, None).unwrap();
+//! ```
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::errors::*;
+use crate::honeybadger::Config;
+use crate::notice::{IntoNotice, Notice};
+use crate::queue::FlushStats;
+use crate::transport::{HyperTransport, Transport};
+
+/// Instance containing an asynchronous client, which delivers notices on its dedicated runtime.
+///
+/// A `Honeybadger` instance is a cheap handle, which may be cloned and shared across threads.
+/// The runtime is shut down when the last clone is dropped, abandoning the notices left in the
+/// background queue, so [`flush`](#method.flush) should be called before the process exits.
+#[derive(Clone)]
+pub struct Honeybadger {
+    honeybadger: crate::Honeybadger,
+}
+
+impl Honeybadger {
+    /// Constructs a blocking Honeybadger instance, and starts its runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - `Config` instance, which is built using the `ConfigBuilder`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use honeybadger::ConfigBuilder;
+    /// # let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_threads(1).build();
+    ///
+    /// assert_eq!(true, honeybadger::blocking::Honeybadger::new(config).is_ok());
+    /// ```
+    pub fn new(config: Config) -> Result<Self> {
+        Honeybadger::with_transport(config, HyperTransport::new())
+    }
+
+    /// Constructs a blocking Honeybadger instance, which sends notices using a custom
+    /// `Transport`, and starts its runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - `Config` instance, which is built using the `ConfigBuilder`
+    /// * `transport` - `Transport` used to deliver the notices
+    pub fn with_transport<T: Transport + 'static>(
+        mut config: Config,
+        transport: T,
+    ) -> Result<Self> {
+        config.dedicated_runtime = true;
+        Ok(Honeybadger {
+            honeybadger: crate::Honeybadger::with_transport(config, transport)?,
+        })
+    }
+
+    /// Send an error to the Honeybadger API, blocking the current thread until the API responded
+    /// or the notice failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - an error that implements the [`IntoNotice`][1] trait,
+    ///   for example a [`notice::Error`][2].
+    /// * `context` - Optional [`HashMap`][3] to pass to the Honeybadger context API
+    ///
+    /// [1]: ../trait.IntoNotice.html
+    /// [2]: ../notice/struct.Error.html
+    /// [3]: https://doc.rust-lang.org/std/collections/struct.HashMap.html
+    pub fn notify<'req, E: IntoNotice>(
+        &self,
+        error: E,
+        context: Option<HashMap<&'req str, &'req str>>,
+    ) -> Result<()> {
+        if error.is_ignored() {
+            return Ok(());
+        }
+        let notice = self.honeybadger.create_notice(error, context);
+        self.send(notice)
+    }
+
+    /// Send a `Notice` to the Honeybadger API, blocking the current thread until the API
+    /// responded or the notice failed.
+    pub fn send(&self, notice: Notice) -> Result<()> {
+        let honeybadger = self.honeybadger.clone();
+        self.honeybadger.block_on(
+            "honeybadger-send",
+            async move { honeybadger.send(notice).await },
+        )
+    }
+
+    /// Queue an error for delivery by the background worker, without waiting on the Honeybadger
+    /// API. See [`Honeybadger::enqueue`](../struct.Honeybadger.html#method.enqueue).
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - an error that implements the [`IntoNotice`][1] trait,
+    ///   for example a [`notice::Error`][2].
+    /// * `context` - Optional [`HashMap`][3] to pass to the Honeybadger context API
+    ///
+    /// [1]: ../trait.IntoNotice.html
+    /// [2]: ../notice/struct.Error.html
+    /// [3]: https://doc.rust-lang.org/std/collections/struct.HashMap.html
+    pub fn enqueue<'req, E: IntoNotice>(
+        &self,
+        error: E,
+        context: Option<HashMap<&'req str, &'req str>>,
+    ) {
+        if error.is_ignored() {
+            return;
+        }
+        let notice = self.honeybadger.create_notice(error, context);
+        let honeybadger = self.honeybadger.clone();
+        let queued = self
+            .honeybadger
+            .block_on("honeybadger-enqueue", async move {
+                honeybadger.enqueue_notice(notice).await;
+                Ok(())
+            });
+        if let Err(e) = queued {
+            error!("Unable to queue Honeybadger notice: {}", e);
+        }
+    }
+
+    /// Deliver the notices in the background queue, blocking the current thread until the queue
+    /// is empty or the deadline expires. See
+    /// [`Honeybadger::flush`](../struct.Honeybadger.html#method.flush).
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - maximum time spent flushing the queue
+    pub fn flush(&self, deadline: Duration) -> FlushStats {
+        let honeybadger = self.honeybadger.clone();
+        let stats = self.honeybadger.block_on("honeybadger-flush", async move {
+            Ok(honeybadger.flush(deadline).await)
+        });
+        match stats {
+            Ok(stats) => stats,
+            Err(e) => {
+                error!("Unable to flush Honeybadger queue: {}", e);
+                FlushStats {
+                    abandoned: self.honeybadger.pending(),
+                    ..Default::default()
+                }
+            }
+        }
+    }
+
+    /// The asynchronous client, for example to install the panic hook with
+    /// [`panic::install`](../panic/fn.install.html).
+    pub fn as_async(&self) -> &crate::Honeybadger {
+        &self.honeybadger
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::blocking::*;
+    use crate::testing::CapturingTransport;
+    use crate::ConfigBuilder;
+    use std::thread;
+
+    #[test]
+    fn test_notify_from_threads() {
+        let config = ConfigBuilder::new("dummy-api-key").with_threads(2).build();
+        let transport = CapturingTransport::new();
+        let honeybadger = Honeybadger::with_transport(config, transport.clone()).unwrap();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let honeybadger = honeybadger.clone();
+                thread::spawn(move || {
+                    let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+                    honeybadger.notify(error, None)
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+
+        assert_eq!(4, transport.notices().len());
+    }
+
+    #[test]
+    fn test_notify_within_runtime() {
+        let config = ConfigBuilder::new("dummy-api-key").with_threads(1).build();
+        let transport = CapturingTransport::new();
+        let honeybadger = Honeybadger::with_transport(config, transport.clone()).unwrap();
+
+        let mut rt = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+            honeybadger.notify(error, None).unwrap();
+        });
+
+        transport.assert_notified("std::io::error::Error");
+    }
+
+    #[test]
+    fn test_enqueue_and_flush() {
+        let config = ConfigBuilder::new("dummy-api-key").with_threads(1).build();
+        let transport = CapturingTransport::new();
+        let honeybadger = Honeybadger::with_transport(config, transport.clone()).unwrap();
+
+        for _ in 0..3 {
+            let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+            honeybadger.enqueue(error, None);
+        }
+        let stats = honeybadger.flush(Duration::from_secs(5));
+
+        assert_eq!(0, stats.abandoned);
+        assert_eq!(3, transport.notices().len());
+    }
+}
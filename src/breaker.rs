@@ -0,0 +1,256 @@
+//! Circuit breaker short-circuiting notices while the Honeybadger API is failing
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// State of the circuit breaker in the delivery path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CircuitState {
+    /// Notices are sent to the Honeybadger API.
+    Closed,
+    /// Notices are short-circuited until the cooldown expires.
+    Open,
+    /// A single probe notice is in flight, to check whether the Honeybadger API has recovered.
+    HalfOpen,
+}
+
+/// Counters of the circuit breaker in the delivery path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CircuitStats {
+    /// Current state of the circuit breaker.
+    pub state: CircuitState,
+    /// Notices that were not sent because the circuit breaker was open.
+    pub short_circuited: u64,
+}
+
+#[derive(Debug)]
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen,
+}
+
+/// Permit to send a notice, returned by [`CircuitBreaker::acquire`]. The outcome of the notice is
+/// recorded through the permit. A half-open probe dropped before its outcome is recorded, e.g.
+/// because its future was cancelled, lets the next notice probe the API instead.
+#[derive(Debug)]
+pub(crate) struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    state: Mutex<State>,
+    threshold: u32,
+    cooldown: Duration,
+    short_circuited: AtomicU64,
+}
+
+impl CircuitBreaker {
+    /// A `threshold` of `0` disables the circuit breaker.
+    pub(crate) fn new(threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreaker {
+            state: Mutex::new(State::Closed { failures: 0 }),
+            threshold,
+            cooldown,
+            short_circuited: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns a permit if a notice may be sent. When the cooldown of an open circuit has
+    /// expired, the caller is let through as the half-open probe.
+    pub(crate) fn acquire(&self) -> Option<Permit<'_>> {
+        let mut state = self.lock();
+        let probe = match *state {
+            State::Closed { .. } => false,
+            State::Open { until } if Instant::now() >= until => {
+                debug!("Honeybadger circuit breaker is half-open, probing the API");
+                *state = State::HalfOpen;
+                true
+            }
+            State::Open { .. } | State::HalfOpen => {
+                self.short_circuited.fetch_add(1, Ordering::SeqCst);
+                return None;
+            }
+        };
+        Some(Permit {
+            breaker: self,
+            probe,
+        })
+    }
+
+    /// The Honeybadger API responded, even if the notice was rejected.
+    pub(crate) fn on_success(&self) {
+        *self.lock() = State::Closed { failures: 0 };
+    }
+
+    /// The Honeybadger API was unavailable or timed out.
+    pub(crate) fn on_failure(&self) {
+        let mut state = self.lock();
+        let failures = match *state {
+            State::Closed { failures } => failures + 1,
+            _ => self.threshold,
+        };
+        if self.threshold > 0 && failures >= self.threshold {
+            self.open(&mut state);
+        } else {
+            *state = State::Closed { failures };
+        }
+    }
+
+    /// The Honeybadger API rate limited the notice.
+    pub(crate) fn on_rate_limited(&self) {
+        if self.threshold > 0 {
+            let mut state = self.lock();
+            self.open(&mut state);
+        }
+    }
+
+    pub(crate) fn stats(&self) -> CircuitStats {
+        CircuitStats {
+            state: match *self.lock() {
+                State::Closed { .. } => CircuitState::Closed,
+                State::Open { .. } => CircuitState::Open,
+                State::HalfOpen => CircuitState::HalfOpen,
+            },
+            short_circuited: self.short_circuited.load(Ordering::SeqCst),
+        }
+    }
+
+    /// The half-open probe was abandoned without an outcome, the next notice probes the API.
+    fn release_probe(&self) {
+        let mut state = self.lock();
+        if let State::HalfOpen = *state {
+            *state = State::Open {
+                until: Instant::now(),
+            };
+        }
+    }
+
+    fn open(&self, state: &mut State) {
+        warn!(
+            "Honeybadger circuit breaker is open, pausing notices for {:?}",
+            self.cooldown
+        );
+        *state = State::Open {
+            until: Instant::now() + self.cooldown,
+        };
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Permit<'_> {
+    /// The Honeybadger API responded, even if the notice was rejected.
+    pub(crate) fn on_success(mut self) {
+        self.probe = false;
+        self.breaker.on_success();
+    }
+
+    /// The Honeybadger API was unavailable or timed out.
+    pub(crate) fn on_failure(mut self) {
+        self.probe = false;
+        self.breaker.on_failure();
+    }
+
+    /// The Honeybadger API rate limited the notice.
+    pub(crate) fn on_rate_limited(mut self) {
+        self.probe = false;
+        self.breaker.on_rate_limited();
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if self.probe {
+            self.breaker.release_probe();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::breaker::*;
+    use std::thread;
+
+    #[test]
+    fn test_open_after_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+        breaker.acquire().unwrap().on_failure();
+        breaker.acquire().unwrap().on_failure();
+
+        assert!(breaker.acquire().is_none());
+        assert_eq!(
+            CircuitStats {
+                state: CircuitState::Open,
+                short_circuited: 1
+            },
+            breaker.stats()
+        );
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+
+        breaker.on_failure();
+        breaker.on_success();
+        breaker.on_failure();
+
+        assert!(breaker.acquire().is_some());
+        assert_eq!(CircuitState::Closed, breaker.stats().state);
+    }
+
+    #[test]
+    fn test_half_open_probe() {
+        let breaker = CircuitBreaker::new(5, Duration::from_millis(10));
+
+        breaker.on_rate_limited();
+        assert!(breaker.acquire().is_none());
+
+        thread::sleep(Duration::from_millis(20));
+        let probe = breaker.acquire().unwrap();
+        assert_eq!(CircuitState::HalfOpen, breaker.stats().state);
+        assert!(breaker.acquire().is_none());
+
+        probe.on_failure();
+        assert_eq!(CircuitState::Open, breaker.stats().state);
+
+        thread::sleep(Duration::from_millis(20));
+        breaker.acquire().unwrap().on_success();
+        assert_eq!(CircuitState::Closed, breaker.stats().state);
+    }
+
+    #[test]
+    fn test_cancelled_probe() {
+        let breaker = CircuitBreaker::new(5, Duration::from_millis(10));
+
+        breaker.on_rate_limited();
+        thread::sleep(Duration::from_millis(20));
+        let probe = breaker.acquire().unwrap();
+        assert!(breaker.acquire().is_none());
+
+        drop(probe);
+        assert_eq!(CircuitState::Open, breaker.stats().state);
+
+        breaker.acquire().unwrap().on_success();
+        assert_eq!(CircuitState::Closed, breaker.stats().state);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let breaker = CircuitBreaker::new(0, Duration::from_secs(60));
+
+        breaker.on_rate_limited();
+        for _ in 0..10 {
+            breaker.on_failure();
+        }
+
+        assert!(breaker.acquire().is_some());
+    }
+}
@@ -1,40 +1,228 @@
 //! Errors used by this package and chained from upstream libraries
-use http;
-use hyper;
-use serde_json;
+use std::error;
+use std::fmt;
 use std::io;
 
-error_chain! {
-    foreign_links {
-        Hyper(hyper::Error);
-        Http(http::Error);
-        Io(io::Error);
-        SerdeJson(serde_json::Error);
-    }
+use http::StatusCode;
 
-    errors {
-        UnauthorizedError {
-            description("API key is incorrect or the account is deactivated")
-        }
-        RateExceededError {
-            description("Honeybadger rate limit exceeded")
+/// Convenient result type, whose error is an [`Error`](enum.Error.html).
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Error returned when a notice could not be delivered to the Honeybadger API.
+///
+/// Errors replied by the Honeybadger API carry the status and the body of the response, see
+/// [`status`](#method.status) and [`body`](#method.body).
+#[derive(Debug)]
+pub enum Error {
+    /// The API key is incorrect or the account is deactivated.
+    Unauthorized { body: String },
+    /// The Honeybadger rate limit is exceeded.
+    RateExceeded { body: String },
+    /// The payload couldn't be processed.
+    NotProcessed { body: String },
+    /// The endpoint replied with a redirect.
+    Redirection { status: u16 },
+    /// The Honeybadger API replied with a '500 Internal Server Error'.
+    Server { body: String },
+    /// The Honeybadger API replied with an unknown status code.
+    UnknownStatusCode { status: u16, body: String },
+    /// The circuit breaker is open, the notice was not sent to the Honeybadger API.
+    CircuitOpen,
+    /// The transport was unable to send the notice.
+    Transport(String),
+    /// The Honeybadger client timed out, after the number of seconds.
+    Timeout(u64),
+    /// A thread or runtime delivering the notice failed.
+    Runtime(String),
+    /// The Hyper client failed.
+    #[cfg(feature = "tokio-runtime")]
+    Hyper(hyper::Error),
+    /// The Isahc client failed.
+    #[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+    Isahc(isahc::Error),
+    /// The HTTP request couldn't be built.
+    Http(http::Error),
+    /// An I/O operation failed.
+    Io(io::Error),
+    /// The notice couldn't be serialized.
+    SerdeJson(serde_json::Error),
+}
+
+impl Error {
+    /// Build the error replied by the Honeybadger API for an unsuccessful response.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status of the response
+    /// * `body` - The body of the response
+    pub fn from_response(status: StatusCode, body: &[u8]) -> Error {
+        let body = String::from_utf8_lossy(body).into_owned();
+        match status {
+            s if s.is_redirection() => Error::Redirection { status: s.as_u16() },
+            StatusCode::UNAUTHORIZED => Error::Unauthorized { body },
+            StatusCode::UNPROCESSABLE_ENTITY => Error::NotProcessed { body },
+            StatusCode::TOO_MANY_REQUESTS => Error::RateExceeded { body },
+            StatusCode::INTERNAL_SERVER_ERROR => Error::Server { body },
+            s => Error::UnknownStatusCode {
+                status: s.as_u16(),
+                body,
+            },
         }
-        NotProcessedError {
-            description("The payload couldn't be processed")
+    }
+
+    /// The HTTP status replied by the Honeybadger API, if the API replied.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Unauthorized { .. } => Some(StatusCode::UNAUTHORIZED.as_u16()),
+            Error::RateExceeded { .. } => Some(StatusCode::TOO_MANY_REQUESTS.as_u16()),
+            Error::NotProcessed { .. } => Some(StatusCode::UNPROCESSABLE_ENTITY.as_u16()),
+            Error::Server { .. } => Some(StatusCode::INTERNAL_SERVER_ERROR.as_u16()),
+            Error::Redirection { status } | Error::UnknownStatusCode { status, .. } => {
+                Some(*status)
+            }
+            _ => None,
         }
-        RedirectionError {
-            description("The endpoint replied with a redirect")
+    }
+
+    /// The body of the response replied by the Honeybadger API, if the API replied.
+    pub fn body(&self) -> Option<&str> {
+        match self {
+            Error::Unauthorized { body }
+            | Error::RateExceeded { body }
+            | Error::NotProcessed { body }
+            | Error::Server { body }
+            | Error::UnknownStatusCode { body, .. } => Some(body),
+            _ => None,
         }
-        ServerError {
-            description("The honeybadger API replied with a '500 Internal Server Error'")
+    }
+
+    /// Whether the notice may be delivered by a later attempt: the Honeybadger API is
+    /// unavailable, times out or is rate limited, or the transport is unable to connect.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Server { .. }
+            | Error::RateExceeded { .. }
+            | Error::Transport(_)
+            | Error::Timeout(_) => true,
+            Error::UnknownStatusCode { status, .. } => *status >= 500,
+            #[cfg(feature = "tokio-runtime")]
+            Error::Hyper(e) => e.is_connect(),
+            #[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+            Error::Isahc(e) => e.is_network() || e.is_timeout(),
+            _ => false,
         }
-        TimeoutError(timeout: u64) {
-            description("Honeybadger client timed out")
-            display("Honeybadger timed out after {} seconds", timeout)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unauthorized { .. } => {
+                write!(f, "API key is incorrect or the account is deactivated")
+            }
+            Error::RateExceeded { .. } => write!(f, "Honeybadger rate limit exceeded"),
+            Error::NotProcessed { .. } => write!(f, "The payload couldn't be processed"),
+            Error::Redirection { .. } => write!(f, "The endpoint replied with a redirect"),
+            Error::Server { .. } => write!(
+                f,
+                "The honeybadger API replied with a '500 Internal Server Error'"
+            ),
+            Error::UnknownStatusCode { status, .. } => write!(
+                f,
+                "Honeybadger responded with an unknown status code: {}",
+                status
+            ),
+            Error::CircuitOpen => write!(
+                f,
+                "The circuit breaker is open, the notice was not sent to the honeybadger API"
+            ),
+            Error::Transport(message) => write!(
+                f,
+                "The transport was unable to send the notice: {}",
+                message
+            ),
+            Error::Timeout(timeout) => write!(f, "Honeybadger timed out after {} seconds", timeout),
+            Error::Runtime(message) => write!(f, "{}", message),
+            #[cfg(feature = "tokio-runtime")]
+            Error::Hyper(e) => write!(f, "{}", e),
+            #[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+            Error::Isahc(e) => write!(f, "{}", e),
+            Error::Http(e) => write!(f, "{}", e),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::SerdeJson(e) => write!(f, "{}", e),
         }
-        UnknownStatusCodeError(status_code: u16) {
-            description("Honeybadger responded with an unknown status code")
-            display("Honeybadger responded with an unknown status code: {}", status_code)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "tokio-runtime")]
+            Error::Hyper(e) => Some(e),
+            #[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+            Error::Isahc(e) => Some(e),
+            Error::Http(e) => Some(e),
+            Error::Io(e) => Some(e),
+            Error::SerdeJson(e) => Some(e),
+            _ => None,
         }
     }
 }
+
+#[cfg(feature = "tokio-runtime")]
+impl From<hyper::Error> for Error {
+    fn from(error: hyper::Error) -> Error {
+        Error::Hyper(error)
+    }
+}
+
+#[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+impl From<isahc::Error> for Error {
+    fn from(error: isahc::Error) -> Error {
+        Error::Isahc(error)
+    }
+}
+
+impl From<http::Error> for Error {
+    fn from(error: http::Error) -> Error {
+        Error::Http(error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Error {
+        Error::SerdeJson(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::errors::*;
+
+    #[test]
+    fn test_from_response() {
+        let error = Error::from_response(StatusCode::UNAUTHORIZED, b"{\"error\":\"Invalid key\"}");
+        assert!(matches!(error, Error::Unauthorized { .. }));
+        assert_eq!(Some(401), error.status());
+        assert_eq!(Some("{\"error\":\"Invalid key\"}"), error.body());
+        assert!(!error.is_retryable());
+
+        let error = Error::from_response(StatusCode::SERVICE_UNAVAILABLE, b"");
+        assert_eq!(Some(503), error.status());
+        assert!(error.is_retryable());
+
+        let error = Error::from_response(StatusCode::MOVED_PERMANENTLY, b"");
+        assert!(matches!(error, Error::Redirection { status: 301 }));
+        assert_eq!(None, error.body());
+
+        assert_eq!(None, Error::Timeout(5).status());
+        assert!(Error::Timeout(5).is_retryable());
+    }
+}
@@ -0,0 +1,127 @@
+//! Spawn, timer and blocking primitives of the async runtime selected by the cargo features
+//!
+//! The `tokio-runtime` feature takes precedence over `async-std-runtime`, which takes
+//! precedence over `smol-runtime`.
+use std::future::Future;
+use std::time::Duration;
+
+use crate::errors::*;
+
+#[cfg(not(any(
+    feature = "tokio-runtime",
+    feature = "async-std-runtime",
+    feature = "smol-runtime"
+)))]
+compile_error!(
+    "one of the `tokio-runtime`, `async-std-runtime` or `smol-runtime` features must be enabled"
+);
+
+/// Spawn a detached task on the current runtime.
+#[cfg(feature = "tokio-runtime")]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(future);
+}
+
+/// Wait on a future until the timeout expires, returning `None` if it expired.
+#[cfg(feature = "tokio-runtime")]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    tokio::time::timeout(duration, future).await.ok()
+}
+
+#[cfg(feature = "tokio-runtime")]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::delay_for(duration).await
+}
+
+/// Run a future to completion on a new single-threaded runtime, blocking the current thread.
+#[cfg(feature = "tokio-runtime")]
+pub(crate) fn block_on<F: Future>(future: F) -> Result<F::Output> {
+    let mut rt = tokio::runtime::Builder::new()
+        .basic_scheduler()
+        .enable_all()
+        .build()?;
+    Ok(rt.block_on(future))
+}
+
+#[cfg(all(feature = "async-std-runtime", not(feature = "tokio-runtime")))]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    async_std::task::spawn(future);
+}
+
+#[cfg(all(feature = "async-std-runtime", not(feature = "tokio-runtime")))]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    async_std::future::timeout(duration, future).await.ok()
+}
+
+#[cfg(all(feature = "async-std-runtime", not(feature = "tokio-runtime")))]
+pub(crate) async fn sleep(duration: Duration) {
+    async_std::task::sleep(duration).await
+}
+
+#[cfg(all(feature = "async-std-runtime", not(feature = "tokio-runtime")))]
+pub(crate) fn block_on<F: Future>(future: F) -> Result<F::Output> {
+    Ok(async_std::task::block_on(future))
+}
+
+#[cfg(all(
+    feature = "smol-runtime",
+    not(any(feature = "tokio-runtime", feature = "async-std-runtime"))
+))]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    smol::spawn(future).detach();
+}
+
+#[cfg(all(
+    feature = "smol-runtime",
+    not(any(feature = "tokio-runtime", feature = "async-std-runtime"))
+))]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    smol::future::or(async { Some(future.await) }, async {
+        smol::Timer::after(duration).await;
+        None
+    })
+    .await
+}
+
+#[cfg(all(
+    feature = "smol-runtime",
+    not(any(feature = "tokio-runtime", feature = "async-std-runtime"))
+))]
+pub(crate) async fn sleep(duration: Duration) {
+    smol::Timer::after(duration).await;
+}
+
+#[cfg(all(
+    feature = "smol-runtime",
+    not(any(feature = "tokio-runtime", feature = "async-std-runtime"))
+))]
+pub(crate) fn block_on<F: Future>(future: F) -> Result<F::Output> {
+    Ok(smol::block_on(future))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::executor::*;
+
+    #[test]
+    fn test_timeout() {
+        let result = block_on(async {
+            let expired = timeout(Duration::from_millis(10), sleep(Duration::from_secs(5))).await;
+            let completed = timeout(Duration::from_secs(5), async { 42 }).await;
+            (expired, completed)
+        })
+        .unwrap();
+
+        assert_eq!((None, Some(42)), result);
+    }
+}
@@ -0,0 +1,138 @@
+//! Report handler for [eyre](https://docs.rs/eyre), recording a backtrace when a report is created
+//!
+//! Reports created while the handler is installed carry the backtrace of the location where they
+//! were created, which is forwarded to Honeybadger when the report is converted into a
+//! [`notice::Error`](../notice/struct.Error.html). Available with the `eyre` feature.
+//!
+//! # Example
+//!
+//! ```rust
+//! # use honeybadger::notice;
+//! honeybadger::eyre_handler::install().unwrap();
+//!
+//! let report = eyre::eyre!("eyre Report");
+//! let error: notice::Error = report.into();
+//! assert!(error.backtrace.is_some());
+//! ```
+use std::error::Error;
+use std::fmt;
+use std::panic::Location;
+
+use backtrace::Backtrace;
+use eyre::{Chain, EyreHandler, InstallError};
+
+use crate::notice::Frame;
+
+/// Install the Honeybadger report handler as the eyre hook. The hook must be installed before
+/// the first report is created, and may only be installed once.
+pub fn install() -> Result<(), InstallError> {
+    eyre::set_hook(Box::new(Handler::default_with))
+}
+
+/// Report handler capturing an unresolved backtrace when a report is created. Symbols are only
+/// resolved when the report is converted into a notice.
+///
+/// Reports are formatted like the default eyre handler, with the location of their creation in
+/// place of the backtrace.
+#[derive(Debug)]
+pub struct Handler {
+    backtrace: Backtrace,
+    location: Option<&'static Location<'static>>,
+}
+
+impl Handler {
+    /// Constructs a handler for a new report, suitable for `eyre::set_hook`.
+    pub fn default_with(_error: &(dyn Error + 'static)) -> Box<dyn EyreHandler> {
+        Box::new(Handler {
+            backtrace: Backtrace::new_unresolved(),
+            location: None,
+        })
+    }
+
+    /// The location where the report was created.
+    pub fn location(&self) -> Option<&'static Location<'static>> {
+        self.location
+    }
+
+    /// Resolve the backtrace recorded when the report was created.
+    pub(crate) fn frames(&self) -> Vec<Frame> {
+        let mut backtrace = self.backtrace.clone();
+        backtrace.resolve();
+        Frame::from_backtrace(&backtrace)
+    }
+}
+
+impl EyreHandler for Handler {
+    fn debug(&self, error: &(dyn Error + 'static), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            return fmt::Debug::fmt(error, f);
+        }
+
+        write!(f, "{}", error)?;
+        for (n, cause) in Chain::new(error).skip(1).enumerate() {
+            if n == 0 {
+                write!(f, "\n\nCaused by:")?;
+            }
+            write!(f, "\n    {}: {}", n, cause)?;
+        }
+        if let Some(location) = self.location {
+            write!(f, "\n\nLocation:\n    {}", location)?;
+        }
+
+        Ok(())
+    }
+
+    fn track_caller(&mut self, location: &'static Location<'static>) {
+        self.location = Some(location);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::eyre_handler::*;
+    use crate::notice;
+    use eyre::WrapErr;
+
+    #[test]
+    fn test_report_into_notice() {
+        let _ = install();
+
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing config");
+        let report = Err::<(), _>(io)
+            .wrap_err("unable to load config")
+            .wrap_err("startup failed")
+            .unwrap_err();
+        let handler = report.handler().downcast_ref::<Handler>().unwrap();
+        assert!(handler
+            .location()
+            .unwrap()
+            .file()
+            .ends_with("eyre_handler.rs"));
+        assert!(format!("{:?}", report).contains("Caused by:"));
+
+        let error: notice::Error = report.into();
+        assert_eq!("std::io::error::Error", error.class);
+        assert_eq!(Some("startup failed".to_owned()), error.message);
+        let causes = error.causes.unwrap();
+        assert_eq!(
+            vec![Some("unable to load config"), Some("missing config")],
+            causes
+                .iter()
+                .map(|cause| cause.message.as_deref())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["eyre::Context", "std::io::error::Error"],
+            causes
+                .iter()
+                .map(|cause| cause.class.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert!(error
+            .backtrace
+            .unwrap()
+            .iter()
+            .any(|frame| frame.method.contains("test_report_into_notice")));
+    }
+}
@@ -0,0 +1,291 @@
+//! Fingerprinters controlling how notices are grouped by Honeybadger
+//!
+//! Honeybadger groups notices by their class and the location of the error, unless a notice
+//! carries a fingerprint, in which case notices with the same fingerprint are grouped together.
+//! A [`Fingerprinter`](trait.Fingerprinter.html) configured with
+//! [`ConfigBuilder::with_fingerprinter`](../struct.ConfigBuilder.html#method.with_fingerprinter)
+//! computes the fingerprint of each notice that does not already carry one, for example from
+//! [`IntoNotice::fingerprint`](../trait.IntoNotice.html#method.fingerprint).
+//!
+//! # Example
+//!
+//! ```rust
+//! # use honeybadger::ConfigBuilder;
+//! use honeybadger::fingerprint::MessageFingerprinter;
+//!
+//! # let api_token = "ffffff";
+//! let config = ConfigBuilder::new(api_token)
+//!     .with_fingerprinter(MessageFingerprinter)
+//!     .build();
+//! ```
+use std::fmt;
+
+use crate::notice::{self, FrameContext};
+
+/// Strategy computing the fingerprint of a notice, from its `error` node.
+///
+/// The error is complete when the fingerprint is computed: its backtrace is normalized against
+/// the project root, so in-project frames are marked with the `app` context. Returning `None`
+/// leaves the grouping to Honeybadger.
+///
+/// The trait is implemented for closures, so a custom strategy may be configured directly:
+///
+/// ```rust
+/// # use honeybadger::ConfigBuilder;
+/// # use honeybadger::notice;
+/// # let api_token = "ffffff";
+/// let config = ConfigBuilder::new(api_token)
+///     .with_fingerprinter(|error: &notice::Error| {
+///         error.tags.first().map(|tag| format!("{} {}", error.class, tag))
+///     })
+///     .build();
+/// ```
+pub trait Fingerprinter: Send + Sync {
+    /// The fingerprint of the error, or `None` to leave the grouping to Honeybadger.
+    fn fingerprint(&self, error: &notice::Error) -> Option<String>;
+}
+
+impl<F> Fingerprinter for F
+where
+    F: Fn(&notice::Error) -> Option<String> + Send + Sync,
+{
+    fn fingerprint(&self, error: &notice::Error) -> Option<String> {
+        self(error)
+    }
+}
+
+impl fmt::Debug for dyn Fingerprinter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Fingerprinter")
+    }
+}
+
+/// Groups notices by their class only, regardless of their message or location.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ClassFingerprinter;
+
+impl Fingerprinter for ClassFingerprinter {
+    fn fingerprint(&self, error: &notice::Error) -> Option<String> {
+        Some(error.class.clone())
+    }
+}
+
+/// Groups notices by their class and their normalized message.
+///
+/// Numbers, UUIDs, hexadecimal values and file paths are replaced by placeholders in the
+/// message, so that `user 42 not found` and `user 7 not found` are grouped together.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MessageFingerprinter;
+
+impl Fingerprinter for MessageFingerprinter {
+    fn fingerprint(&self, error: &notice::Error) -> Option<String> {
+        Some(match error.message {
+            Some(ref message) => format!("{}: {}", error.class, normalize_message(message)),
+            None => error.class.clone(),
+        })
+    }
+}
+
+/// Groups notices by their class and the topmost frame of their backtrace belonging to the
+/// project, ignoring the line number so that edits elsewhere in the file keep the grouping.
+/// Notices without an in-project frame are grouped by their class.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameFingerprinter;
+
+impl Fingerprinter for FrameFingerprinter {
+    fn fingerprint(&self, error: &notice::Error) -> Option<String> {
+        let frame = error
+            .backtrace
+            .iter()
+            .flatten()
+            .find(|frame| frame.context == Some(FrameContext::App));
+        Some(match frame {
+            Some(frame) => format!("{} {}:{}", error.class, frame.file, frame.method),
+            None => error.class.clone(),
+        })
+    }
+}
+
+/// Characters trimmed around a word before it is classified, e.g. quotes and trailing
+/// punctuation.
+const WORD_DELIMITERS: &[char] = &[
+    '"', '\'', '`', '(', ')', '[', ']', '{', '}', '<', '>', ',', ';', ':', '.', '!', '?',
+];
+
+/// Replace the variable parts of a message by placeholders: file paths by `<path>`, UUIDs by
+/// `<uuid>`, hexadecimal values by `<hex>` and numbers by `<n>`.
+pub(crate) fn normalize_message(message: &str) -> String {
+    message
+        .split(' ')
+        .map(|word| {
+            let core = word.trim_matches(WORD_DELIMITERS);
+            if core.is_empty() {
+                return word.to_owned();
+            }
+            let start = word.len() - word.trim_start_matches(WORD_DELIMITERS).len();
+            let (prefix, rest) = word.split_at(start);
+            let suffix = &rest[core.len()..];
+            let normalized = if is_path(core) {
+                "<path>".to_owned()
+            } else if is_uuid(core) {
+                "<uuid>".to_owned()
+            } else if is_hex(core) {
+                "<hex>".to_owned()
+            } else {
+                replace_numbers(core)
+            };
+            format!("{}{}{}", prefix, normalized, suffix)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_path(word: &str) -> bool {
+    word.starts_with('/')
+        || word.starts_with("./")
+        || word.starts_with("../")
+        || word.starts_with("~/")
+        || word.contains('\\')
+        || (word.contains('/') && word.contains('.'))
+}
+
+fn is_uuid(word: &str) -> bool {
+    let groups: Vec<&str> = word.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip(&[8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == *len && is_hex_digits(group))
+}
+
+/// A `0x` prefixed value, or a long run of hexadecimal digits such as a hash or an id.
+fn is_hex(word: &str) -> bool {
+    match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        Some(digits) => !digits.is_empty() && is_hex_digits(digits),
+        None => word.len() >= 8 && is_hex_digits(word) && word.chars().any(|c| c.is_ascii_digit()),
+    }
+}
+
+fn is_hex_digits(word: &str) -> bool {
+    word.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn replace_numbers(word: &str) -> String {
+    let mut normalized = String::with_capacity(word.len());
+    let mut in_number = false;
+    for c in word.chars() {
+        if c.is_ascii_digit() {
+            if !in_number {
+                normalized.push_str("<n>");
+                in_number = true;
+            }
+        } else {
+            normalized.push(c);
+            in_number = false;
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::fingerprint::*;
+    use crate::notice::Frame;
+
+    fn error(message: &str, backtrace: Option<Vec<Frame>>) -> notice::Error {
+        notice::Error {
+            class: "my_app::Error".to_owned(),
+            message: Some(message.to_owned()),
+            causes: None,
+            backtrace,
+            fingerprint: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn frame(file: &str, method: &str, context: FrameContext) -> Frame {
+        Frame {
+            number: "12".to_owned(),
+            file: file.to_owned(),
+            method: method.to_owned(),
+            column: None,
+            context: Some(context),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_message() {
+        assert_eq!(
+            "user <n> not found in <path>",
+            normalize_message("user 42 not found in /var/lib/users.db")
+        );
+        assert_eq!(
+            "order <uuid> failed at <hex> (<hex>)",
+            normalize_message(
+                "order 3f2504e0-4f89-11d3-9a0c-0305e82c3301 failed at 0x7ffd (deadbeef42)"
+            )
+        );
+        assert_eq!(
+            "retry <n>/<n>: shard<n> timed out after <n>.<n>s",
+            normalize_message("retry 2/5: shard7 timed out after 1.5s")
+        );
+        assert_eq!(
+            "unable to open \"<path>\"",
+            normalize_message("unable to open \"C:\\data\\config.toml\"")
+        );
+        assert_eq!(
+            "connection refused",
+            normalize_message("connection refused")
+        );
+    }
+
+    #[test]
+    fn test_builtin_fingerprinters() {
+        let first = error("user 42 not found", None);
+        let second = error("user 7 not found", None);
+
+        assert_eq!(
+            Some("my_app::Error".to_owned()),
+            ClassFingerprinter.fingerprint(&first)
+        );
+        assert_eq!(
+            Some("my_app::Error: user <n> not found".to_owned()),
+            MessageFingerprinter.fingerprint(&first)
+        );
+        assert_eq!(
+            MessageFingerprinter.fingerprint(&first),
+            MessageFingerprinter.fingerprint(&second)
+        );
+        assert_eq!(
+            Some("my_app::Error".to_owned()),
+            FrameFingerprinter.fingerprint(&first)
+        );
+
+        let traced = error(
+            "user 42 not found",
+            Some(vec![
+                frame(
+                    "/cargo/std/src/panicking.rs",
+                    "std::panicking",
+                    FrameContext::All,
+                ),
+                frame(
+                    "[PROJECT_ROOT]/src/users.rs",
+                    "my_app::users::find",
+                    FrameContext::App,
+                ),
+                frame(
+                    "[PROJECT_ROOT]/src/main.rs",
+                    "my_app::main",
+                    FrameContext::App,
+                ),
+            ]),
+        );
+        assert_eq!(
+            Some("my_app::Error [PROJECT_ROOT]/src/users.rs:my_app::users::find".to_owned()),
+            FrameFingerprinter.fingerprint(&traced)
+        );
+    }
+}
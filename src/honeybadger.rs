@@ -2,23 +2,49 @@ use std::collections::HashMap;
 use std::convert::From;
 use std::env;
 use std::fmt;
+use std::future::Future;
 use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::Arc;
+use std::thread;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use backtrace::Backtrace;
+use http::Request;
 use http::StatusCode;
-use hyper::client::{HttpConnector};
-use hyper::{Body, Client, Request};
-use hyper_tls::HttpsConnector;
+use serde_json::Value;
 
+use crate::breaker::{CircuitBreaker, CircuitStats};
 use crate::errors::*;
-use crate::notice;
+use crate::executor;
+use crate::fingerprint::Fingerprinter;
+use crate::notice::{self, IntoNotice};
+use crate::queue::{FlushStats, OverflowPolicy, Queue, QueueStats};
+#[cfg(feature = "tokio-runtime")]
+use crate::runtime::DeliveryRuntime;
+use crate::source::SourceCache;
+use crate::spool::Spool;
+#[cfg(feature = "tokio-runtime")]
+use crate::transport::HyperTransport;
+#[cfg(not(feature = "tokio-runtime"))]
+use crate::transport::IsahcTransport;
+use crate::transport::Transport;
 use notice::{Notice, Notifier};
 
 const HONEYBADGER_ENDPOINT: &'static str = "/v1/notices";
 const HONEYBADGER_DEFAULT_TIMEOUT: u64 = 5;
 const HONEYBADGER_DEFAULT_THREADS: usize = 4;
+const HONEYBADGER_DEFAULT_SOURCE_RADIUS: usize = 2;
+const HONEYBADGER_DEFAULT_QUEUE_CAPACITY: usize = 100;
+const HONEYBADGER_DEFAULT_MAX_RETRIES: u32 = 2;
+const HONEYBADGER_DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+const HONEYBADGER_MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+const HONEYBADGER_DEFAULT_BREAKER_THRESHOLD: u32 = 5;
+const HONEYBADGER_DEFAULT_BREAKER_COOLDOWN: Duration = Duration::from_secs(30);
+const HONEYBADGER_DEFAULT_SPOOL_MAX_BYTES: u64 = 10 * 1024 * 1024;
+const HONEYBADGER_DEFAULT_SPOOL_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+const HONEYBADGER_DELAYED_TAG: &str = "delayed";
 const HONEYBADGER_SERVER_URL: &'static str = "https://api.honeybadger.io";
 
 const NOTIFIER_NAME: &'static str = "honeybadger";
@@ -35,7 +61,22 @@ pub struct Config {
     hostname: String,
     endpoint: String,
     timeout: Duration,
+    #[cfg_attr(not(feature = "tokio-runtime"), allow(dead_code))]
     threads: usize,
+    #[cfg(feature = "tokio-runtime")]
+    pub(crate) dedicated_runtime: bool,
+    source_radius: usize,
+    queue_capacity: usize,
+    overflow_policy: OverflowPolicy,
+    max_retries: u32,
+    retry_backoff: Duration,
+    breaker_threshold: u32,
+    breaker_cooldown: Duration,
+    spool_dir: Option<String>,
+    spool_max_bytes: u64,
+    spool_max_age: Duration,
+    crash_dir: Option<String>,
+    fingerprinter: Option<Arc<dyn Fingerprinter>>,
 }
 
 /// Configuration builder struct, used for building a `Config` instance
@@ -47,13 +88,49 @@ pub struct ConfigBuilder {
     endpoint: Option<String>,
     timeout: Option<Duration>,
     threads: Option<usize>,
+    #[cfg(feature = "tokio-runtime")]
+    dedicated_runtime: Option<bool>,
+    source_radius: Option<usize>,
+    queue_capacity: Option<usize>,
+    overflow_policy: Option<OverflowPolicy>,
+    max_retries: Option<u32>,
+    retry_backoff: Option<Duration>,
+    breaker_threshold: Option<u32>,
+    breaker_cooldown: Option<Duration>,
+    spool_dir: Option<String>,
+    spool_max_bytes: Option<u64>,
+    spool_max_age: Option<Duration>,
+    crash_dir: Option<String>,
+    fingerprinter: Option<Arc<dyn Fingerprinter>>,
 }
 
 /// Instance containing the client connection and user configuration for this crate.
+///
+/// A `Honeybadger` instance is a cheap handle, which may be cloned and shared across threads and
+/// tasks. Clones share the same configuration, transport and background queue.
+#[derive(Clone)]
 pub struct Honeybadger {
-    client: Arc<Client<HttpsConnector<HttpConnector>>>,
-    config: Config,
-    user_agent: String,
+    transport: Arc<dyn Transport>,
+    config: Arc<Config>,
+    user_agent: Arc<str>,
+    source_cache: Arc<SourceCache>,
+    queue: Arc<Queue>,
+    breaker: Arc<CircuitBreaker>,
+    spool: Option<Arc<Spool>>,
+    crash_spool: Option<Arc<Spool>>,
+    #[cfg(feature = "tokio-runtime")]
+    runtime: Option<DeliveryRuntime>,
+}
+
+/// Guard which flushes the background queue of a `Honeybadger` instance when dropped.
+///
+/// The guard is returned by [`Honeybadger::init`](struct.Honeybadger.html#method.init), and is
+/// meant to be held until the end of `main`, so that queued notices are delivered before the
+/// process exits.
+pub struct FlushGuard {
+    honeybadger: Honeybadger,
+    deadline: Duration,
+    flushed: bool,
 }
 
 impl ConfigBuilder {
@@ -66,6 +143,9 @@ impl ConfigBuilder {
     ///   - `HOSTNAME` - host name for each event.
     ///   - `HONEYBADGER_ENDPOINT` - override the default endpoint for the HTTPS client.
     ///   - `HONEYBADGER_TIMEOUT` - write timeout for the Honeybadger HTTPS client.
+    ///   - `HONEYBADGER_SOURCE_RADIUS` - number of source lines around each backtrace line.
+    ///   - `HONEYBADGER_SPOOL_DIR` - directory where undeliverable notices are persisted.
+    ///   - `HONEYBADGER_CRASH_DIR` - directory where panic notices are persisted.
     ///
     /// # Arguments
     ///
@@ -90,6 +170,22 @@ impl ConfigBuilder {
                 .and_then(|s| s.parse().ok())
                 .map(|t| Duration::new(t, 0)),
             threads: None,
+            #[cfg(feature = "tokio-runtime")]
+            dedicated_runtime: None,
+            source_radius: env::var("HONEYBADGER_SOURCE_RADIUS")
+                .ok()
+                .and_then(|s| s.parse().ok()),
+            queue_capacity: None,
+            overflow_policy: None,
+            max_retries: None,
+            retry_backoff: None,
+            breaker_threshold: None,
+            breaker_cooldown: None,
+            spool_dir: env::var("HONEYBADGER_SPOOL_DIR").ok(),
+            spool_max_bytes: None,
+            spool_max_age: None,
+            crash_dir: env::var("HONEYBADGER_CRASH_DIR").ok(),
+            fingerprinter: None,
         }
     }
 
@@ -191,7 +287,10 @@ impl ConfigBuilder {
     }
 
     /// Override the number of threads the async HTTP connection should use to queue Honeybadger
-    /// payloads.  Consumes the `ConfigBuilder` and returns a new reference.
+    /// payloads. The threads run the dedicated runtime enabled with
+    /// [`with_dedicated_runtime`](#method.with_dedicated_runtime), and the runtime owned by a
+    /// [`blocking::Honeybadger`](blocking/struct.Honeybadger.html) instance. Consumes the
+    /// `ConfigBuilder` and returns a new reference.
     ///
     /// # Arguments
     ///
@@ -209,6 +308,278 @@ impl ConfigBuilder {
         self
     }
 
+    /// Deliver notices on a runtime owned by the `Honeybadger` instance, with the number of worker
+    /// threads set by [`with_threads`](#method.with_threads), instead of the runtime of the
+    /// caller. Error reporting then never competes with the tasks of the application, and
+    /// notices are still delivered while the runtime of the application is shutting down.
+    /// Requires the `tokio-runtime` feature. Consumes the `ConfigBuilder` and returns a new
+    /// value.
+    ///
+    /// # Arguments
+    ///
+    /// * `dedicated_runtime` - Whether notices are delivered on a dedicated runtime
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token)
+    ///     .with_threads(2)
+    ///     .with_dedicated_runtime(true);
+    /// ```
+    #[cfg(feature = "tokio-runtime")]
+    pub fn with_dedicated_runtime(mut self, dedicated_runtime: bool) -> Self {
+        self.dedicated_runtime = Some(dedicated_runtime);
+        self
+    }
+
+    /// Override the number of source code lines sent before and after each line of the backtrace
+    /// that belongs to the project. Source files are read relative to the project root, and
+    /// skipped if they are missing. Consumes the `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `radius` - The number of lines to send on either side of a backtrace line
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_source_radius(5);
+    /// ```
+    pub fn with_source_radius(mut self, radius: usize) -> Self {
+        self.source_radius = Some(radius);
+        self
+    }
+
+    /// Override the number of notices that may be waiting in the background queue, when using
+    /// [`Honeybadger::enqueue`](struct.Honeybadger.html#method.enqueue). Consumes the
+    /// `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - The maximum number of queued notices
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_queue_capacity(1000);
+    /// ```
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = Some(capacity);
+        self
+    }
+
+    /// Override the behaviour of the background queue when a notice is queued while the queue is
+    /// full. Consumes the `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The `OverflowPolicy` to apply on a full queue
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::{ConfigBuilder, OverflowPolicy};
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_overflow_policy(OverflowPolicy::DropOldest);
+    /// ```
+    pub fn with_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow_policy = Some(policy);
+        self
+    }
+
+    /// Override the number of times a notice is retried, when the Honeybadger API is unavailable,
+    /// times out or is rate limited. Notices rejected because of an invalid API key or an invalid
+    /// payload are never retried. Consumes the `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `retries` - The maximum number of retries, or `0` to disable retries
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_max_retries(5);
+    /// ```
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = Some(retries);
+        self
+    }
+
+    /// Override the delay before the first retry, which doubles on every subsequent retry.
+    /// Consumes the `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `backoff` - A `Duration` reference specifying the delay before the first retry
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// # use std::time::Duration;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_retry_backoff(&Duration::from_secs(1));
+    /// ```
+    pub fn with_retry_backoff(mut self, backoff: &Duration) -> Self {
+        self.retry_backoff = Some(backoff.to_owned());
+        self
+    }
+
+    /// Override the number of consecutive failures after which the circuit breaker opens. While
+    /// the circuit is open, notices are not sent to the Honeybadger API, until a probe notice
+    /// succeeds after the cooldown. A rate limited notice opens the circuit immediately.
+    /// Consumes the `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The number of consecutive failures, or `0` to disable the circuit breaker
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_breaker_threshold(10);
+    /// ```
+    pub fn with_breaker_threshold(mut self, threshold: u32) -> Self {
+        self.breaker_threshold = Some(threshold);
+        self
+    }
+
+    /// Override the time the circuit breaker stays open, before a probe notice is sent to the
+    /// Honeybadger API. Consumes the `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `cooldown` - A `Duration` reference specifying how long the circuit stays open
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// # use std::time::Duration;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_breaker_cooldown(&Duration::from_secs(60));
+    /// ```
+    pub fn with_breaker_cooldown(mut self, cooldown: &Duration) -> Self {
+        self.breaker_cooldown = Some(cooldown.to_owned());
+        self
+    }
+
+    /// Enable the spool, a directory where notices that could not be delivered because the
+    /// Honeybadger API was unavailable are persisted. Spooled notices are replayed in order, when
+    /// the `Honeybadger` instance is constructed, by the background worker when it starts, and
+    /// after the next notice is delivered successfully.
+    /// Consumes the `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `spool_dir` - The directory where notices are persisted
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_spool_dir("/var/spool/honeybadger");
+    /// ```
+    pub fn with_spool_dir(mut self, spool_dir: &str) -> Self {
+        self.spool_dir = Some(spool_dir.to_owned());
+        self
+    }
+
+    /// Override the maximum total size of the spooled notices, after which the oldest notices
+    /// are evicted. Consumes the `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_bytes` - The maximum size of the spool in bytes
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_spool_max_bytes(1024 * 1024);
+    /// ```
+    pub fn with_spool_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.spool_max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Override the maximum age of a spooled notice, after which it is evicted. Consumes the
+    /// `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age` - A `Duration` reference specifying the maximum age of a spooled notice
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// # use std::time::Duration;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_spool_max_age(&Duration::from_secs(3600));
+    /// ```
+    pub fn with_spool_max_age(mut self, max_age: &Duration) -> Self {
+        self.spool_max_age = Some(max_age.to_owned());
+        self
+    }
+
+    /// Enable the crash spool, a directory where the panic hook installed by
+    /// [`panic::install`](panic/fn.install.html) persists each panic notice before delivering it.
+    /// A panic notice is still pending if the process aborted or exited before it was delivered,
+    /// and is then delivered by the next `Honeybadger` instance constructed with the same
+    /// directory, tagged as `delayed`. Consumes the `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `crash_dir` - The directory where panic notices are persisted
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_crash_dir("/var/lib/honeybadger/crashes");
+    /// ```
+    pub fn with_crash_dir(mut self, crash_dir: &str) -> Self {
+        self.crash_dir = Some(crash_dir.to_owned());
+        self
+    }
+
+    /// Override the strategy computing the fingerprint of each notice, which controls how notices
+    /// are grouped by Honeybadger. Notices whose error already carries a fingerprint keep it.
+    /// Consumes the `ConfigBuilder` and returns a new value.
+    ///
+    /// # Arguments
+    ///
+    /// * `fingerprinter` - The [`Fingerprinter`](fingerprint/trait.Fingerprinter.html) applied to
+    ///   each notice
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::ConfigBuilder;
+    /// use honeybadger::fingerprint::FrameFingerprinter;
+    ///
+    /// let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).with_fingerprinter(FrameFingerprinter);
+    /// ```
+    pub fn with_fingerprinter<F: Fingerprinter + 'static>(mut self, fingerprinter: F) -> Self {
+        self.fingerprinter = Some(Arc::new(fingerprinter));
+        self
+    }
+
     /// Prepare a `Config` instance for constructing a Honeybadger instance.
     ///
     /// Defaults are set if the `ConfigBuilder` used to construct the `Config` is empty.
@@ -218,6 +589,19 @@ impl ConfigBuilder {
     ///   - _default endpoint_: `https://api.honeybadger.io/v1/notices`
     ///   - _default timeout_: a 5 second client write timeout
     ///   - _default threads_: 4 threads are used in the asynchronous runtime pool
+    ///   - _default dedicated runtime_: notices are delivered on the runtime of the caller
+    ///   - _default source radius_: 2 lines of source code around each backtrace line
+    ///   - _default queue capacity_: 100 notices are kept in the background queue
+    ///   - _default overflow policy_: the newest notice is dropped when the queue is full
+    ///   - _default max retries_: a notice is retried twice
+    ///   - _default retry backoff_: the first retry is attempted after 500 milliseconds
+    ///   - _default breaker threshold_: the circuit opens after 5 consecutive failures
+    ///   - _default breaker cooldown_: the circuit stays open for 30 seconds
+    ///   - _default spool dir_: no spool, undeliverable notices are dropped
+    ///   - _default spool max bytes_: 10 MiB of spooled notices
+    ///   - _default spool max age_: spooled notices are kept for 24 hours
+    ///   - _default crash dir_: no crash spool, panic notices are only delivered immediately
+    ///   - _default fingerprinter_: none, notices are grouped by Honeybadger
     ///
     /// # Example
     ///
@@ -238,21 +622,65 @@ impl ConfigBuilder {
             env: self.env.unwrap_or_else(|| "".to_owned()),
             hostname: self
                 .hostname
-                .or(hostname::get().ok().map(|s| s.to_string_lossy().to_string()))
+                .or(hostname::get()
+                    .ok()
+                    .map(|s| s.to_string_lossy().to_string()))
                 .unwrap_or_else(|| "".to_owned()),
-            endpoint: self
-                .endpoint
-                .unwrap_or_else(|| format!("{}{}", if cfg!(test) { mockito::server_url() } else { String::from(HONEYBADGER_SERVER_URL) }, HONEYBADGER_ENDPOINT ) ),
+            endpoint: self.endpoint.unwrap_or_else(|| {
+                format!(
+                    "{}{}",
+                    if cfg!(test) {
+                        mockito::server_url()
+                    } else {
+                        String::from(HONEYBADGER_SERVER_URL)
+                    },
+                    HONEYBADGER_ENDPOINT
+                )
+            }),
             timeout: self
                 .timeout
                 .unwrap_or_else(|| Duration::new(HONEYBADGER_DEFAULT_TIMEOUT, 0)),
             threads: self.threads.unwrap_or(HONEYBADGER_DEFAULT_THREADS),
+            #[cfg(feature = "tokio-runtime")]
+            dedicated_runtime: self.dedicated_runtime.unwrap_or(false),
+            source_radius: self
+                .source_radius
+                .unwrap_or(HONEYBADGER_DEFAULT_SOURCE_RADIUS),
+            queue_capacity: self
+                .queue_capacity
+                .unwrap_or(HONEYBADGER_DEFAULT_QUEUE_CAPACITY),
+            overflow_policy: self.overflow_policy.unwrap_or(OverflowPolicy::DropNewest),
+            max_retries: self.max_retries.unwrap_or(HONEYBADGER_DEFAULT_MAX_RETRIES),
+            retry_backoff: self
+                .retry_backoff
+                .unwrap_or(HONEYBADGER_DEFAULT_RETRY_BACKOFF),
+            breaker_threshold: self
+                .breaker_threshold
+                .unwrap_or(HONEYBADGER_DEFAULT_BREAKER_THRESHOLD),
+            breaker_cooldown: self
+                .breaker_cooldown
+                .unwrap_or(HONEYBADGER_DEFAULT_BREAKER_COOLDOWN),
+            spool_dir: self.spool_dir,
+            spool_max_bytes: self
+                .spool_max_bytes
+                .unwrap_or(HONEYBADGER_DEFAULT_SPOOL_MAX_BYTES),
+            spool_max_age: self
+                .spool_max_age
+                .unwrap_or(HONEYBADGER_DEFAULT_SPOOL_MAX_AGE),
+            crash_dir: self.crash_dir,
+            fingerprinter: self.fingerprinter,
         }
     }
 }
 
 impl Honeybadger {
     /// Constructs a Honeybadger instance, which may be used to send API notify requests.
+    /// Notices are sent with the [`HyperTransport`](struct.HyperTransport.html) with the
+    /// `tokio-runtime` feature, and with the [`IsahcTransport`](struct.IsahcTransport.html) with
+    /// the `async-std-runtime` or `smol-runtime` features.
+    ///
+    /// If a spool or a crash spool is configured, the notices left over by a previous process are
+    /// delivered on a background thread.
     ///
     /// # Arguments
     ///
@@ -268,10 +696,43 @@ impl Honeybadger {
     /// assert_eq!(true, Honeybadger::new(config).is_ok());
     /// ```
     pub fn new(config: Config) -> Result<Self> {
-        let https = HttpsConnector::new();
+        #[cfg(feature = "tokio-runtime")]
+        let transport = HyperTransport::new();
+        #[cfg(not(feature = "tokio-runtime"))]
+        let transport = IsahcTransport::new()?;
+        Honeybadger::with_transport(config, transport)
+    }
 
-        let builder = Client::builder();
+    /// Constructs a Honeybadger instance, which sends notices using a custom `Transport` instead
+    /// of the default HTTPS client.
+    ///
+    /// If a spool or a crash spool is configured, the notices left over by a previous process are
+    /// delivered on a background thread.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - `Config` instance, which is built using the `ConfigBuilder`
+    /// * `transport` - `Transport` used to deliver the notices
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use honeybadger::{ConfigBuilder, Honeybadger, HyperTransport};
+    /// # let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).build();
+    /// let transport = HyperTransport::new();
+    ///
+    /// assert_eq!(true, Honeybadger::with_transport(config, transport).is_ok());
+    /// ```
+    pub fn with_transport<T: Transport + 'static>(config: Config, transport: T) -> Result<Self> {
+        let honeybadger = Honeybadger::construct(config, transport)?;
+        honeybadger.deliver_spooled();
+        Ok(honeybadger)
+    }
 
+    /// Constructs a Honeybadger instance, without delivering the notices left in the spools by a
+    /// previous process.
+    pub(crate) fn construct<T: Transport + 'static>(config: Config, transport: T) -> Result<Self> {
         let os = os_type::current_platform();
         let user_agent: String = fmt::format(format_args!(
             "HB-rust {}; {:?}/{}",
@@ -283,22 +744,76 @@ impl Honeybadger {
             config
         );
 
-        Ok(Honeybadger {
-            config: config,
-            client: Arc::new(builder.build(https)),
-            user_agent: user_agent,
-        })
+        let queue = Queue::new(config.queue_capacity, config.overflow_policy);
+        let breaker = CircuitBreaker::new(config.breaker_threshold, config.breaker_cooldown);
+        let spool = config.spool_dir.as_ref().map(|dir| {
+            Arc::new(Spool::new(
+                Path::new(dir),
+                config.spool_max_bytes,
+                config.spool_max_age,
+            ))
+        });
+        #[cfg(feature = "tokio-runtime")]
+        let runtime = if config.dedicated_runtime {
+            Some(DeliveryRuntime::new(config.threads)?)
+        } else {
+            None
+        };
+        let crash_spool = config.crash_dir.as_ref().map(|dir| {
+            Arc::new(Spool::new(
+                Path::new(dir),
+                config.spool_max_bytes,
+                config.spool_max_age,
+            ))
+        });
+
+        let honeybadger = Honeybadger {
+            config: Arc::new(config),
+            transport: Arc::new(transport),
+            user_agent: user_agent.into(),
+            source_cache: Arc::new(SourceCache::default()),
+            queue: Arc::new(queue),
+            breaker: Arc::new(breaker),
+            spool,
+            crash_spool,
+            #[cfg(feature = "tokio-runtime")]
+            runtime,
+        };
+        Ok(honeybadger)
+    }
+
+    /// Constructs a Honeybadger instance, together with a `FlushGuard` which delivers the notices
+    /// left in the background queue when it goes out of scope.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - `Config` instance, which is built using the `ConfigBuilder`
+    /// * `deadline` - maximum time spent delivering the remaining notices on shutdown
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use honeybadger::{ConfigBuilder, Honeybadger};
+    /// # use std::time::Duration;
+    /// # let api_token = "ffffff";
+    /// let config = ConfigBuilder::new(api_token).build();
+    /// let (honeybadger, _guard) = Honeybadger::init(config, Duration::from_secs(2)).unwrap();
+    /// ```
+    pub fn init(config: Config, deadline: Duration) -> Result<(Self, FlushGuard)> {
+        let honeybadger = Honeybadger::new(config)?;
+        let guard = FlushGuard::new(honeybadger.clone(), deadline);
+        Ok((honeybadger, guard))
     }
 
-    fn serialize<'req>(
+    fn create_notice_with_config(
         config: &Config,
         error: notice::Error,
-        context: Option<HashMap<&'req str, &'req str>>,
-    ) -> serde_json::Result<Vec<u8>> {
+        context: Option<HashMap<String, Value>>,
+    ) -> Notice {
         let notifier = Notifier {
-            name: NOTIFIER_NAME,
-            url: NOTIFIER_URL,
-            version: VERSION,
+            name: NOTIFIER_NAME.to_owned(),
+            url: NOTIFIER_URL.to_owned(),
+            version: VERSION.to_owned(),
         };
 
         let request = notice::Request {
@@ -307,9 +822,9 @@ impl Honeybadger {
         };
 
         let server = notice::Server {
-            project_root: &config.root,
-            environment_name: &config.env,
-            hostname: &config.hostname,
+            project_root: config.root.clone(),
+            environment_name: config.env.clone(),
+            hostname: config.hostname.clone(),
             time: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .map(|v| v.as_secs())
@@ -317,43 +832,32 @@ impl Honeybadger {
             pid: process::id(),
         };
 
-        let notice = Notice {
-            api_key: &config.api_key,
+        Notice {
+            api_key: config.api_key.clone(),
             notifier: notifier,
             error: error,
             request: request,
             server: server,
-        };
-
-        serde_json::to_vec(&notice)
+        }
     }
 
-    fn create_payload_with_config<'req>(
+    fn create_payload_with_config(
         config: &Config,
         user_agent: &str,
-        error: notice::Error,
-        context: Option<HashMap<&'req str, &'req str>>,
-    ) -> Result<Request<Body>> {
-        let api_key: &str = config.api_key.as_ref();
-        let user_agent: &str = user_agent.as_ref();
-
-        let data = Honeybadger::serialize(config, error, context)?;
+        notice: &Notice,
+    ) -> Result<Request<Vec<u8>>> {
+        let data = serde_json::to_vec(notice)?;
         let r = Request::builder()
             .uri(config.endpoint.clone())
             .method(http::Method::POST)
             .header(http::header::ACCEPT, "application/json")
-            .header("X-API-Key", api_key)
+            .header("X-API-Key", notice.api_key.as_str())
             .header(http::header::USER_AGENT, user_agent)
-            .body(Body::from(data))?;
+            .body(data)?;
 
         Ok(r)
     }
 
-    fn convert_error(kind: ErrorKind) -> Error {
-        let e: Result<()> = Err(kind.into());
-        e.err().unwrap()
-    }
-
     /// Trigger the notify request using an async HTTPS request.
     ///
     /// Requires an initialized [Tokio][1] `Runtime`, and returns a [Future][2] that must be
@@ -361,22 +865,19 @@ impl Honeybadger {
     ///
     /// # Arguments
     ///
-    /// * `error` - a struct that implements the [`From`][4] trait for a
-    /// [`notice::Error`][5].
+    /// * `error` - an error that implements the [`IntoNotice`][4] trait,
+    ///   for example a [`notice::Error`][5].
     /// * `context` - Optional [`HashMap`][7] to pass to the [Honeybadger context][6] API
     ///
     /// # Examples
     ///
-    /// ## With `chained_error::Error`
+    /// ## With `std::error::Error`
     ///
-    /// ```rust
-    /// #[macro_use] extern crate error_chain;
-    /// error_chain! {
-    ///   errors {
-    ///     MyCustomError
-    ///   }
-    /// }
-    /// #
+    /// Errors of the standard library are accepted as they are, while other types implementing
+    /// [`std::error::Error`][8] are wrapped in a [`notice::StdError`][9]. A
+    /// `Box<std::error::Error>` is converted into a `notice::Error` first.
+    ///
+    /// ```rust, no_run
     /// # fn main() {
     /// # use honeybadger::{ConfigBuilder, Honeybadger};
     /// # use tokio::runtime::Runtime;
@@ -384,169 +885,621 @@ impl Honeybadger {
     /// # let config = ConfigBuilder::new(api_token).build();
     /// # let mut honeybadger = Honeybadger::new(config).unwrap();
     ///
-    /// let error : Result<()> = Err(ErrorKind::MyCustomError.into());
+    /// let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+    /// let wrapped = std::fmt::Error;
+    /// let boxed: Box<dyn std::error::Error> = "std Error".into();
     ///
     /// let mut rt = Runtime::new().unwrap();
-    /// let future = honeybadger.notify(
-    ///   honeybadger::notice::Error::new(&error.unwrap_err()),
-    ///   None);
-    ///
-    /// rt.block_on(future);
+    /// rt.block_on(honeybadger.notify(error, None)).unwrap();
+    /// rt.block_on(honeybadger.notify(honeybadger::notice::StdError(wrapped), None)).unwrap();
+    /// rt.block_on(honeybadger.notify(honeybadger::notice::Error::from(boxed), None)).unwrap();
     /// #
     /// # }
     /// ```
     ///
-    /// ## With `failure::Error`
+    /// ## With `failure::Error` or `error_chain::Error`
     ///
-    /// ```rust, no_run
-    /// #[macro_use] extern crate failure;
-    /// #[derive(Fail, Debug)]
-    /// #[fail(display = "Failure error")]
-    /// struct MyCustomError;
-    /// # fn main() {
-    /// # use honeybadger::{ConfigBuilder, Honeybadger};
-    /// # use tokio::runtime::Runtime;
-    /// # let api_token = "ffffff";
-    /// # let config = ConfigBuilder::new(api_token).build();
-    /// # let mut honeybadger = Honeybadger::new(config).unwrap();
+    /// With the `failure` or `error-chain` features, errors of these crates are converted into a
+    /// `notice::Error` first, see [`notice::Error`][5].
     ///
-    /// let error: Result<(), failure::Error> = Err(MyCustomError {}.into());
+    /// [1]: https://github.com/tokio-rs/tokio
+    /// [2]: https://docs.rs/futures/0.2.1/futures/future/index.html
+    /// [3]: https://docs.rs/hyper/0.12.5/hyper/struct.Request.html
+    /// [4]: trait.IntoNotice.html
+    /// [5]: notice/struct.Error.html
+    /// [6]: https://docs.honeybadger.io/ruby/getting-started/adding-context-to-errors.html#context-in-honeybadger-notify
+    /// [7]: https://doc.rust-lang.org/std/collections/struct.HashMap.html
+    /// [8]: https://doc.rust-lang.org/std/error/trait.Error.html
+    /// [9]: notice/struct.StdError.html
+    pub async fn notify<'req, E: IntoNotice>(
+        &self,
+        error: E,
+        context: Option<HashMap<&'req str, &'req str>>,
+    ) -> Result<()> {
+        if error.is_ignored() {
+            return Ok(());
+        }
+        let notice = self.create_notice(error, context);
+        self.send(notice).await
+    }
+
+    /// Create an owned `Notice`, which may be queued or persisted, and sent at a later time using
+    /// [`send`](#method.send).
     ///
-    /// let mut rt = Runtime::new().unwrap();
-    /// let future = honeybadger.notify(
-    ///   error.unwrap_err(),
-    ///   None);
+    /// A backtrace is captured if the error does not carry one. The backtrace is normalized
+    /// against the project root, and source code is attached to the lines of the project.
     ///
-    /// rt.block_on(future).unwrap();
-    /// #
-    /// # }
-    /// ```
+    /// # Arguments
     ///
-    /// ## With `Box<std::error::Error>`.
+    /// * `error` - an error that implements the [`IntoNotice`][1] trait,
+    ///   for example a [`notice::Error`][2].
+    /// * `context` - Optional [`HashMap`][3] to pass to the [Honeybadger context][4] API
     ///
-    /// Note that [`std::error::Error`](8) does not implement [Sync](9), and it's not possible to
-    /// use the error type across future combinators, so it's recommended to convert into a
-    /// `Box<std::error::Error>` in the same closure as the Honeybadger API call.
+    /// # Example
+    ///
+    /// ```rust
+    /// # use honeybadger::{ConfigBuilder, Honeybadger};
+    /// # let api_token = "ffffff";
+    /// # let config = ConfigBuilder::new(api_token).build();
+    /// # let honeybadger = Honeybadger::new(config).unwrap();
+    /// let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+    /// let notice = honeybadger.create_notice(error, None);
+    ///
+    /// let persisted = serde_json::to_string(&notice).unwrap();
+    /// ```
+    ///
+    /// [1]: trait.IntoNotice.html
+    /// [2]: notice/struct.Error.html
+    /// [3]: https://doc.rust-lang.org/std/collections/struct.HashMap.html
+    /// [4]: https://docs.honeybadger.io/ruby/getting-started/adding-context-to-errors.html#context-in-honeybadger-notify
+    pub fn create_notice<'req, E: IntoNotice>(
+        &self,
+        error: E,
+        context: Option<HashMap<&'req str, &'req str>>,
+    ) -> Notice {
+        let mut merged = error.context();
+        let mut error = error.into_notice_error();
+        if error.backtrace.is_none() {
+            error.backtrace = Some(notice::Frame::from_backtrace(&Backtrace::new()));
+        }
+        error.normalize_backtrace(&self.config.root);
+        self.source_cache
+            .attach(&mut error, &self.config.root, self.config.source_radius);
+        if error.fingerprint.is_none() {
+            if let Some(ref fingerprinter) = self.config.fingerprinter {
+                error.fingerprint = fingerprinter.fingerprint(&error);
+            }
+        }
+
+        merged.extend(
+            context
+                .into_iter()
+                .flatten()
+                .map(|(k, v)| (k.to_owned(), Value::from(v))),
+        );
+        let context = Some(merged).filter(|context| !context.is_empty());
+
+        Honeybadger::create_notice_with_config(&self.config, error, context)
+    }
+
+    /// Send a `Notice` to the Honeybadger API using an async HTTPS request.
+    ///
+    /// Requires an initialized [Tokio][1] `Runtime`, unless a dedicated runtime is configured. The
+    /// notice is not sent while the circuit breaker is open, and a `CircuitOpenError` is returned
+    /// instead.
+    ///
+    /// If a spool is configured, the notice is persisted when the Honeybadger API is unavailable,
+    /// and the error is still returned. After a notice is delivered, the spooled notices are
+    /// replayed in a background task.
+    ///
+    /// # Arguments
+    ///
+    /// * `notice` - a `Notice`, created using [`create_notice`](#method.create_notice) or
+    ///   deserialized from a previously persisted notice
+    ///
+    /// [1]: https://github.com/tokio-rs/tokio
+    pub async fn send(&self, notice: Notice) -> Result<()> {
+        #[cfg(feature = "tokio-runtime")]
+        {
+            if let Some(ref runtime) = self.runtime {
+                let honeybadger = self.detached();
+                return runtime
+                    .run(async move { honeybadger.deliver(notice).await })
+                    .await?;
+            }
+        }
+        self.deliver(notice).await
+    }
+
+    /// Send a notice on the runtime of the caller, spooling it on failure.
+    async fn deliver(&self, notice: Notice) -> Result<()> {
+        let result = self.try_send(&notice).await;
+        if let Some(ref spool) = self.spool {
+            match result {
+                Ok(_) => self.replay_spool(),
+                Err(ref e) if Honeybadger::is_spoolable(e) => {
+                    if let Err(e) = spool.write(&notice) {
+                        error!("Unable to spool Honeybadger notice: {}", e);
+                    }
+                }
+                Err(_) => {}
+            }
+        }
+        result
+    }
+
+    /// Send a notice through the circuit breaker, without spooling it on failure.
+    async fn try_send(&self, notice: &Notice) -> Result<()> {
+        let permit = match self.breaker.acquire() {
+            Some(permit) => permit,
+            None => return Err(Error::CircuitOpen),
+        };
+
+        let result = Honeybadger::notify_with_transport(
+            self.transport.as_ref(),
+            &self.config,
+            &self.user_agent,
+            notice,
+        )
+        .await;
+        match result {
+            Err(Error::RateExceeded { .. }) => permit.on_rate_limited(),
+            Err(ref e) if e.is_retryable() => permit.on_failure(),
+            // the API responded, even if the notice was rejected
+            _ => permit.on_success(),
+        }
+        result
+    }
+
+    /// Replay the spooled notices in a background task, unless a replay is already running.
+    fn replay_spool(&self) {
+        let spool = match self.spool.as_ref().and_then(|spool| spool.start_replay()) {
+            Some(spool) => spool,
+            None => return,
+        };
+        let honeybadger = self.detached();
+        self.spawn(async move {
+            spool.evict();
+            honeybadger.replay(&spool, spool.list(), None).await;
+        });
+    }
+
+    /// Deliver the notices left in the spools by a previous process, on a background thread.
+    /// The panic notices of the crash spool are tagged as `delayed`. Each spool is delivered on
+    /// start once per process, by the first instance using it.
+    fn deliver_spooled(&self) {
+        let start = |spool: &Option<Arc<Spool>>| {
+            spool
+                .as_ref()
+                .filter(|spool| spool.claim_startup())
+                .and_then(|spool| spool.start_replay())
+        };
+        let crashes = start(&self.crash_spool);
+        let spooled = start(&self.spool);
+        if crashes.is_none() && spooled.is_none() {
+            return;
+        }
+        // notices written later by the panic hook of this process are delivered by the hook
+        let crash_paths = crashes
+            .as_ref()
+            .map(|spool| {
+                spool.evict();
+                spool.list()
+            })
+            .unwrap_or_default();
+        let honeybadger = self.clone();
+
+        let spawned = thread::Builder::new()
+            .name("honeybadger-spool".to_owned())
+            .spawn(move || {
+                let replay = async {
+                    if let Some(ref spool) = crashes {
+                        let tag = Some(HONEYBADGER_DELAYED_TAG);
+                        honeybadger.replay(spool, crash_paths, tag).await;
+                    }
+                    if let Some(ref spool) = spooled {
+                        spool.evict();
+                        honeybadger.replay(spool, spool.list(), None).await;
+                    }
+                };
+                if let Err(e) = executor::block_on(replay) {
+                    error!("Unable to deliver spooled Honeybadger notices: {}", e);
+                }
+            });
+        if let Err(e) = spawned {
+            error!("Unable to deliver spooled Honeybadger notices: {}", e);
+        }
+    }
+
+    /// Send spooled notices in order, removing each notice once the Honeybadger API responded.
+    /// Stops at the first notice which should be kept in the spool.
+    async fn replay(&self, spool: &Spool, paths: Vec<PathBuf>, tag: Option<&str>) {
+        for path in paths {
+            let mut notice = match spool.read(&path) {
+                Some(notice) => notice,
+                None => continue,
+            };
+            if let Some(tag) = tag {
+                notice.error.tags.push(tag.to_owned());
+            }
+            match self.try_send(&notice).await {
+                Err(ref e) if Honeybadger::is_spoolable(e) => {
+                    warn!("Stopped replaying the Honeybadger spool: {}", e);
+                    break;
+                }
+                Err(e) => {
+                    error!("Discarding spooled notice {}: {}", path.display(), e);
+                    spool.remove(&path);
+                }
+                Ok(_) => spool.remove(&path),
+            }
+        }
+    }
+
+    /// Deliver a panic notice, persisting it in the crash spool first, if one is configured. The
+    /// persisted notice is removed once the Honeybadger API responded, otherwise it is delivered
+    /// by the next `Honeybadger` instance.
+    pub(crate) async fn send_crash(&self, notice: Notice) -> Result<()> {
+        let spool = match self.crash_spool {
+            Some(ref spool) => spool,
+            None => return self.send(notice).await,
+        };
+        let path = match spool.write(&notice) {
+            Ok(path) => path,
+            Err(e) => {
+                error!("Unable to persist Honeybadger crash notice: {}", e);
+                return self.send(notice).await;
+            }
+        };
+
+        let result = self.try_send(&notice).await;
+        match result {
+            Err(ref e) if Honeybadger::is_spoolable(e) => {}
+            _ => spool.remove(&path),
+        }
+        result
+    }
+
+    /// Queue a notice for delivery by a background worker, without waiting on the Honeybadger API.
+    ///
+    /// The worker is spawned on the current [Tokio][1] `Runtime` when the first notice is queued,
+    /// or on the dedicated runtime if one is configured.
+    /// If the queue is full, the configured `OverflowPolicy` either drops a notice, or waits until
+    /// the worker has made room in the queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - an error that implements the [`IntoNotice`][2] trait,
+    ///   for example a [`notice::Error`][3].
+    /// * `context` - Optional [`HashMap`][4] to pass to the Honeybadger context API
+    ///
+    /// # Example
     ///
     /// ```rust, no_run
-    /// # fn main() {
     /// # use honeybadger::{ConfigBuilder, Honeybadger};
     /// # use tokio::runtime::Runtime;
     /// # let api_token = "ffffff";
     /// # let config = ConfigBuilder::new(api_token).build();
-    /// # let mut honeybadger = Honeybadger::new(config).unwrap();
-    ///
-    /// let error: Result<(), Box<std::error::Error>> = Err(
-    ///   std::io::Error::new(
-    ///     std::io::ErrorKind::Other, "std Error"
-    ///   ).into()
-    /// );
+    /// # let honeybadger = Honeybadger::new(config).unwrap();
+    /// let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
     ///
     /// let mut rt = Runtime::new().unwrap();
-    /// let future = honeybadger.notify(
-    ///   error.unwrap_err(),
-    ///   None);
-    ///
-    /// rt.block_on(future).unwrap();
-    /// #
-    /// # }
+    /// rt.block_on(honeybadger.enqueue(error, None));
     /// ```
     ///
     /// [1]: https://github.com/tokio-rs/tokio
-    /// [2]: https://docs.rs/futures/0.2.1/futures/future/index.html
-    /// [3]: https://docs.rs/hyper/0.12.5/hyper/struct.Request.html
-    /// [4]: https://doc.rust-lang.org/std/convert/trait.From.html
-    /// [5]: notice/struct.Error.html
-    /// [6]: https://docs.honeybadger.io/ruby/getting-started/adding-context-to-errors.html#context-in-honeybadger-notify
-    /// [7]: https://doc.rust-lang.org/std/collections/struct.HashMap.html
-    /// [8]: https://doc.rust-lang.org/std/error/trait.Error.html
-    /// [9]: https://doc.rust-lang.org/std/marker/trait.Sync.html
-    pub async fn notify<'req, E: Into<notice::Error>>(
-        self,
+    /// [2]: trait.IntoNotice.html
+    /// [3]: notice/struct.Error.html
+    /// [4]: https://doc.rust-lang.org/std/collections/struct.HashMap.html
+    pub async fn enqueue<'req, E: IntoNotice>(
+        &self,
         error: E,
         context: Option<HashMap<&'req str, &'req str>>,
-    ) -> Result<()>
+    ) {
+        if error.is_ignored() {
+            return;
+        }
+        let notice = self.create_notice(error, context);
+        self.enqueue_notice(notice).await
+    }
+
+    /// Queue an existing `Notice` for delivery by the background worker, see
+    /// [`enqueue`](#method.enqueue).
+    pub async fn enqueue_notice(&self, notice: Notice) {
+        if self.queue.start() {
+            self.replay_spool();
+            let honeybadger = self.detached();
+            self.spawn(self.queue.clone().drain(move |notice| {
+                let honeybadger = honeybadger.clone();
+                async move { honeybadger.send(notice).await }
+            }));
+        }
+        self.queue.push(notice).await
+    }
+
+    /// Deliver the notices in the background queue and wait for the notices in flight, until the
+    /// deadline expires.
+    ///
+    /// Returns the number of notices delivered or failed while flushing, and the number of
+    /// notices abandoned in the queue when the deadline expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - maximum time spent flushing the queue
+    pub async fn flush(&self, deadline: Duration) -> FlushStats {
+        self.queue.flush(deadline, |notice| self.send(notice)).await
+    }
+
+    /// State of the circuit breaker, and the number of notices it short-circuited.
+    pub fn circuit_stats(&self) -> CircuitStats {
+        self.breaker.stats()
+    }
+
+    /// Counters of the notices dropped, delivered and failed by the background queue.
+    pub fn queue_stats(&self) -> QueueStats {
+        self.queue.stats()
+    }
+
+    /// Number of notices queued or in flight in the background queue.
+    #[cfg_attr(not(feature = "tokio-runtime"), allow(dead_code))]
+    pub(crate) fn pending(&self) -> u64 {
+        self.queue.pending()
+    }
+
+    /// Spawn a task on the dedicated runtime if one is configured, or on the current runtime.
+    fn spawn<F>(&self, future: F)
     where
-        notice::Error: From<E>,
+        F: Future<Output = ()> + Send + 'static,
     {
-        let t = self.config.timeout.as_secs();
-        let request = Honeybadger::create_payload_with_config(
-            &self.config,
-            &self.user_agent,
-            error.into(),
-            context,
-        )?;
-        Ok(Honeybadger::notify_with_client(&self.client, t, request).await?)
+        #[cfg(feature = "tokio-runtime")]
+        {
+            if let Some(ref runtime) = self.runtime {
+                return runtime.spawn(future);
+            }
+        }
+        executor::spawn(future)
     }
 
-    async fn notify_with_client<'req, C>(
-        client: &Client<C>,
-        timeout: u64,
-        request: Request<Body>,
-    ) -> Result<()>
+    /// Run a future to completion on the dedicated runtime if one is configured, or on a new
+    /// thread and runtime, blocking the current thread. A worker thread of the dedicated runtime,
+    /// e.g. a panicking task, uses a new thread and runtime as well.
+    pub(crate) fn block_on<F, T>(&self, name: &str, future: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>> + Send + 'static,
+        T: Send + 'static,
+    {
+        #[cfg(feature = "tokio-runtime")]
+        {
+            match self.runtime {
+                Some(ref runtime) if !DeliveryRuntime::is_worker_thread() => {
+                    return runtime.block_on(future)?;
+                }
+                _ => {}
+            }
+        }
+        Honeybadger::block_on_thread(name, move || future)
+    }
+
+    /// A clone for the tasks spawned on the dedicated runtime, which does not keep the runtime
+    /// alive, so that the runtime is shut down when the last clone held by the application is
+    /// dropped.
+    fn detached(&self) -> Honeybadger {
+        Honeybadger {
+            #[cfg(feature = "tokio-runtime")]
+            runtime: self.runtime.as_ref().map(DeliveryRuntime::detached),
+            ..self.clone()
+        }
+    }
+
+    /// Run a future to completion on a dedicated thread and runtime, blocking the current thread.
+    /// This is safe to call from within an asynchronous runtime, and while it is shutting down.
+    fn block_on_thread<F, R, T>(name: &str, future: F) -> Result<T>
     where
-        C: hyper::client::connect::Connect + Sync + 'static + Clone + Send,
+        F: FnOnce() -> R + Send + 'static,
+        R: Future<Output = Result<T>>,
+        T: Send + 'static,
     {
-        let req = client
-            .request(request);
+        let handle = thread::Builder::new()
+            .name(name.to_owned())
+            .spawn(move || -> Result<T> { executor::block_on(future())? })?;
+
+        handle.join().unwrap_or_else(|_| {
+            Err(Error::Runtime(format!(
+                "Honeybadger thread {} panicked",
+                name
+            )))
+        })
+    }
 
-        let response = match tokio::time::timeout(Duration::from_secs(timeout), req).await {
-            Ok(v) => v.map_err(|err| Honeybadger::convert_error(ErrorKind::Hyper(err))),
-            Err(_) => Err(Honeybadger::convert_error(ErrorKind::TimeoutError(timeout))),
+    /// Post a notice, retrying with a jittered exponential backoff when the Honeybadger API is
+    /// unavailable, times out or is rate limited. The `Retry-After` header of a rate limited
+    /// response takes precedence over the backoff, unless it exceeds the maximum backoff, in which
+    /// case the notice is not retried.
+    async fn notify_with_transport(
+        transport: &dyn Transport,
+        config: &Config,
+        user_agent: &str,
+        notice: &Notice,
+    ) -> Result<()> {
+        let t = config.timeout.as_secs();
+        let mut attempt = 0;
+        loop {
+            let request = Honeybadger::create_payload_with_config(config, user_agent, notice)?;
+            let (result, retry_after) =
+                match Honeybadger::request_with_transport(transport, t, request).await {
+                    Ok(response) => (
+                        Honeybadger::check_status(&response),
+                        Honeybadger::retry_after(response.headers(), response.status()),
+                    ),
+                    Err(e) => (Err(e), None),
+                };
+
+            let delay = match result {
+                Err(ref e) if attempt < config.max_retries && e.is_retryable() => {
+                    let delay = retry_after
+                        .unwrap_or_else(|| Honeybadger::backoff(config.retry_backoff, attempt));
+                    // give up on a distant `Retry-After`, the notice is spooled instead
+                    if delay > HONEYBADGER_MAX_RETRY_BACKOFF {
+                        warn!("Honeybadger notify failed: {}, retry after {:?}", e, delay);
+                        return result;
+                    }
+                    warn!("Honeybadger notify failed: {}, retrying in {:?}", e, delay);
+                    delay
+                }
+                result => return result,
+            };
+            executor::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn request_with_transport(
+        transport: &dyn Transport,
+        timeout: u64,
+        request: Request<Vec<u8>>,
+    ) -> Result<http::Response<Vec<u8>>> {
+        let req = transport.send(request);
+
+        let response = match executor::timeout(Duration::from_secs(timeout), req).await {
+            Some(v) => v,
+            None => Err(Error::Timeout(timeout)),
         }?;
 
-        let (parts, _) = response.into_parts();
-        debug!("Honeybadger API returned status: {}", parts.status);
-        match parts.status {
-            s if s.is_success() => Ok(()),
-            s if s.is_redirection() => Err(ErrorKind::RedirectionError.into()),
-            StatusCode::UNAUTHORIZED => Err(ErrorKind::UnauthorizedError.into()),
-            StatusCode::UNPROCESSABLE_ENTITY => Err(ErrorKind::NotProcessedError.into()),
-            StatusCode::TOO_MANY_REQUESTS => Err(ErrorKind::RateExceededError.into()),
-            StatusCode::INTERNAL_SERVER_ERROR => Err(ErrorKind::ServerError.into()),
-            _ => Err(ErrorKind::UnknownStatusCodeError(parts.status.as_u16()).into()),
+        debug!("Honeybadger API returned status: {}", response.status());
+        Ok(response)
+    }
+
+    fn check_status(response: &http::Response<Vec<u8>>) -> Result<()> {
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(Error::from_response(response.status(), response.body()))
+        }
+    }
+
+    /// Parse the `Retry-After` header of a rate limited response, either in seconds or as an HTTP
+    /// date.
+    fn retry_after(headers: &http::HeaderMap, status: StatusCode) -> Option<Duration> {
+        if status != StatusCode::TOO_MANY_REQUESTS {
+            return None;
+        }
+        let value = headers.get(http::header::RETRY_AFTER)?.to_str().ok()?;
+        match value.trim().parse::<u64>() {
+            Ok(seconds) => Some(Duration::from_secs(seconds)),
+            Err(_) => httpdate::parse_http_date(value)
+                .ok()
+                .map(|date| date.duration_since(SystemTime::now()).unwrap_or_default()),
+        }
+    }
+
+    fn is_spoolable(error: &Error) -> bool {
+        match error {
+            Error::CircuitOpen => true,
+            _ => error.is_retryable(),
         }
     }
+
+    /// Exponential backoff with jitter, between half and the full delay of the attempt.
+    fn backoff(base: Duration, attempt: u32) -> Duration {
+        let delay = base
+            .checked_mul(2u32.saturating_pow(attempt))
+            .unwrap_or(HONEYBADGER_MAX_RETRY_BACKOFF)
+            .min(HONEYBADGER_MAX_RETRY_BACKOFF);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|v| v.subsec_nanos())
+            .unwrap_or(0);
+        let half = delay / 2;
+        half + half.mul_f64(f64::from(nanos % 1000) / 1000.0)
+    }
 }
 
-#[cfg(test)]
+impl FlushGuard {
+    pub(crate) fn new(honeybadger: Honeybadger, deadline: Duration) -> FlushGuard {
+        FlushGuard {
+            honeybadger,
+            deadline,
+            flushed: false,
+        }
+    }
+
+    /// Flush the background queue, blocking the current thread until the queue is empty or the
+    /// deadline of the guard expires.
+    pub fn flush(mut self) -> FlushStats {
+        self.flush_blocking()
+    }
+
+    fn flush_blocking(&mut self) -> FlushStats {
+        self.flushed = true;
+        let honeybadger = self.honeybadger.clone();
+        let deadline = self.deadline;
+
+        let stats = self.honeybadger.block_on("honeybadger-flush", async move {
+            Ok(honeybadger.flush(deadline).await)
+        });
+
+        match stats {
+            Ok(stats) => {
+                debug!("Flushed Honeybadger queue: {:?}", stats);
+                stats
+            }
+            Err(e) => {
+                error!("Unable to flush Honeybadger queue: {}", e);
+                FlushStats {
+                    abandoned: self.honeybadger.queue.pending(),
+                    ..Default::default()
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FlushGuard {
+    fn drop(&mut self) {
+        if !self.flushed {
+            self.flush_blocking();
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio-runtime"))]
 mod tests {
 
     use crate::honeybadger::*;
-    use hyper::client::Client;
+    use hyper::client::{Client, HttpConnector};
     use hyper::Body;
+    use mockito::mock;
     use std::time::Duration;
     use tokio::runtime::Runtime;
-    use mockito::mock;
 
-    fn test_client_with_response(status: usize, config: &Config) -> Result<()> {
+    fn test_client_with_response(status: usize, config: Config) -> Result<()> {
         let _m = mock("POST", HONEYBADGER_ENDPOINT)
             .with_status(status)
             .with_header("Content-Type", "application/json")
             .create();
 
+        test_client(config)
+    }
+
+    fn test_client(config: Config) -> Result<()> {
         let mut http_connector = HttpConnector::new();
         http_connector.enforce_http(false);
         let client = Client::builder().build::<HttpConnector, Body>(http_connector);
+        let honeybadger =
+            Honeybadger::with_transport(config, HyperTransport::with_client(client)).unwrap();
 
         let mut rt = Runtime::new().unwrap();
 
-        let error: Result<()> = Err(ErrorKind::RedirectionError.into());
-        let error = notice::Error::new(&error.unwrap_err());
-        let req =
-            Honeybadger::create_payload_with_config(config, "test-client", error, None).unwrap();
-        let t = config.timeout.as_secs();
-        let res = Honeybadger::notify_with_client(&client, t, req);
+        let error = Error::Redirection { status: 301 };
+        let notice = honeybadger.create_notice(notice::StdError(error), None);
 
-        rt.block_on(res)
+        rt.block_on(honeybadger.send(notice))
     }
 
     #[test]
     fn test_notify_ok() {
         let config = ConfigBuilder::new("dummy-api-key").build();
-        let res = test_client_with_response(201,
-            &config,
-        );
+        let res = test_client_with_response(201, config);
 
         assert_eq!((), res.unwrap());
     }
@@ -554,17 +1507,308 @@ mod tests {
     #[test]
     fn test_notify_rate_exceeded() {
         let config = ConfigBuilder::new("dummy-api-key").build();
-        let res = test_client_with_response(
-            429,
-            &config,
-        );
+        let res = test_client_with_response(429, config);
 
         match res {
-            Err(Error(ErrorKind::RateExceededError, _)) => assert!(true),
+            Err(Error::RateExceeded { .. }) => assert!(true),
             _ => assert_eq!("", "expected rate exceeded error, but was not"),
         }
     }
 
+    #[test]
+    fn test_clone_handle() {
+        fn assert_handle<T: Clone + Send + Sync + 'static>() {}
+        assert_handle::<Honeybadger>();
+
+        let config = ConfigBuilder::new("dummy-api-key").build();
+        let honeybadger = Honeybadger::new(config).unwrap();
+        let cloned = honeybadger.clone();
+
+        assert!(Arc::ptr_eq(&honeybadger.transport, &cloned.transport));
+        assert!(Arc::ptr_eq(&honeybadger.queue, &cloned.queue));
+    }
+
+    #[test]
+    fn test_notify_retry_server_error() {
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_max_retries(2)
+            .with_retry_backoff(&Duration::from_millis(1))
+            .build();
+        let m = mock("POST", HONEYBADGER_ENDPOINT)
+            .with_status(503)
+            .expect(3)
+            .create();
+
+        let res = test_client(config);
+
+        m.assert();
+        assert!(matches!(
+            res,
+            Err(Error::UnknownStatusCode { status: 503, .. })
+        ));
+    }
+
+    #[test]
+    fn test_notify_no_retry_unauthorized() {
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_max_retries(2)
+            .with_retry_backoff(&Duration::from_millis(1))
+            .build();
+        let m = mock("POST", HONEYBADGER_ENDPOINT)
+            .with_status(401)
+            .with_body("{\"error\":\"Invalid API key\"}")
+            .expect(1)
+            .create();
+
+        let res = test_client(config);
+
+        m.assert();
+        let error = res.unwrap_err();
+        assert!(matches!(error, Error::Unauthorized { .. }));
+        assert_eq!(Some(401), error.status());
+        assert_eq!(Some("{\"error\":\"Invalid API key\"}"), error.body());
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let response = http::Response::builder()
+            .status(429)
+            .header(http::header::RETRY_AFTER, "7")
+            .body(Vec::<u8>::new())
+            .unwrap();
+
+        assert_eq!(
+            Some(Duration::from_secs(7)),
+            Honeybadger::retry_after(response.headers(), response.status())
+        );
+    }
+
+    #[test]
+    fn test_notify_no_retry_distant_retry_after() {
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_max_retries(2)
+            .with_breaker_threshold(0)
+            .build();
+        let m = mock("POST", HONEYBADGER_ENDPOINT)
+            .with_status(429)
+            .with_header("retry-after", "3600")
+            .expect(1)
+            .create();
+
+        let start = std::time::Instant::now();
+        let res = test_client(config);
+
+        m.assert();
+        assert!(matches!(res, Err(Error::RateExceeded { .. })));
+        assert!(start.elapsed() < HONEYBADGER_MAX_RETRY_BACKOFF);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let base = Duration::from_millis(100);
+
+        for attempt in 0..3 {
+            let delay = Honeybadger::backoff(base, attempt);
+            assert!(delay >= base * 2u32.pow(attempt) / 2);
+            assert!(delay <= base * 2u32.pow(attempt));
+        }
+        assert!(Honeybadger::backoff(base, 100) <= HONEYBADGER_MAX_RETRY_BACKOFF);
+    }
+
+    #[test]
+    fn test_send_circuit_open() {
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_max_retries(0)
+            .with_endpoint("http://127.0.0.1:1/")
+            .with_breaker_threshold(1)
+            .build();
+        let honeybadger = Honeybadger::new(config).unwrap();
+        let mut rt = Runtime::new().unwrap();
+
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+        let notice = honeybadger.create_notice(error, None);
+        let first = rt.block_on(honeybadger.send(notice.clone()));
+        let second = rt.block_on(honeybadger.send(notice));
+
+        assert!(matches!(first, Err(Error::Hyper(_))));
+        assert!(matches!(second, Err(Error::CircuitOpen)));
+        assert_eq!(1, honeybadger.circuit_stats().short_circuited);
+    }
+
+    #[test]
+    fn test_send_spool_and_replay() {
+        let spool_dir = env::temp_dir().join(format!("honeybadger-send-spool-{}", process::id()));
+        let _ = std::fs::remove_dir_all(&spool_dir);
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_max_retries(0)
+            .with_breaker_threshold(0)
+            .with_spool_dir(spool_dir.to_str().unwrap())
+            .build();
+        let honeybadger = Honeybadger::new(config).unwrap();
+        let spool = honeybadger.spool.clone().unwrap();
+
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+        let notice = honeybadger.create_notice(error, None);
+        {
+            let _m = mock("POST", HONEYBADGER_ENDPOINT).with_status(503).create();
+            let mut rt = Runtime::new().unwrap();
+            assert!(rt.block_on(honeybadger.send(notice.clone())).is_err());
+        }
+        assert_eq!(1, spool.list().len());
+
+        // a new runtime discards the connection pooled by the previous runtime
+        let mut rt = Runtime::new().unwrap();
+        let m = mock("POST", HONEYBADGER_ENDPOINT)
+            .with_status(201)
+            .expect(2)
+            .create();
+        rt.block_on(async {
+            honeybadger.send(notice).await.unwrap();
+            while !spool.list().is_empty() {
+                tokio::time::delay_for(Duration::from_millis(1)).await;
+            }
+        });
+
+        m.assert();
+        std::fs::remove_dir_all(&spool_dir).unwrap();
+    }
+
+    #[test]
+    fn test_deliver_crashes_on_start() {
+        let crash_dir = env::temp_dir().join(format!("honeybadger-crash-{}", process::id()));
+        let _ = std::fs::remove_dir_all(&crash_dir);
+        let config = ConfigBuilder::new("dummy-api-key").build();
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+        let notice =
+            Honeybadger::create_notice_with_config(&config, error.into_notice_error(), None);
+        let crash_spool = Spool::new(&crash_dir, 1024 * 1024, Duration::from_secs(60));
+        crash_spool.write(&notice).unwrap();
+
+        let m = mock("POST", HONEYBADGER_ENDPOINT)
+            .match_body(mockito::Matcher::Regex(
+                r#""tags":\["delayed"\]"#.to_owned(),
+            ))
+            .with_status(201)
+            .expect(1)
+            .create();
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_crash_dir(crash_dir.to_str().unwrap())
+            .build();
+        let _honeybadger = Honeybadger::new(config).unwrap();
+        while !crash_spool.list().is_empty() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        m.assert();
+        std::fs::remove_dir_all(&crash_dir).unwrap();
+    }
+
+    #[test]
+    fn test_deliver_spool_on_start() {
+        let spool_dir = env::temp_dir().join(format!("honeybadger-start-spool-{}", process::id()));
+        let _ = std::fs::remove_dir_all(&spool_dir);
+        let config = ConfigBuilder::new("dummy-api-key").build();
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "spooled Error");
+        let notice =
+            Honeybadger::create_notice_with_config(&config, error.into_notice_error(), None);
+        let spool = Spool::new(&spool_dir, 1024 * 1024, Duration::from_secs(60));
+        spool.write(&notice).unwrap();
+
+        let m = mock("POST", HONEYBADGER_ENDPOINT)
+            .match_body(mockito::Matcher::Regex("spooled Error".to_owned()))
+            .with_status(201)
+            .expect(1)
+            .create();
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_spool_dir(spool_dir.to_str().unwrap())
+            .build();
+        let _honeybadger = Honeybadger::new(config).unwrap();
+        while !spool.list().is_empty() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        m.assert();
+        std::fs::remove_dir_all(&spool_dir).unwrap();
+    }
+
+    struct ThreadTransport(std::sync::Mutex<Vec<Option<String>>>);
+
+    impl Transport for Arc<ThreadTransport> {
+        fn send(&self, _: Request<Vec<u8>>) -> crate::TransportFuture<'_> {
+            let name = thread::current().name().map(|name| name.to_owned());
+            self.0.lock().unwrap().push(name);
+            Box::pin(async { Ok(http::Response::builder().status(201).body(Vec::new())?) })
+        }
+    }
+
+    #[test]
+    fn test_send_dedicated_runtime() {
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_threads(1)
+            .with_dedicated_runtime(true)
+            .build();
+        let transport = Arc::new(ThreadTransport(Default::default()));
+        let honeybadger = Honeybadger::with_transport(config, transport.clone()).unwrap();
+        let mut rt = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .build()
+            .unwrap();
+
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+        let notice = honeybadger.create_notice(error, None);
+        rt.block_on(honeybadger.send(notice)).unwrap();
+
+        assert_eq!(
+            vec![Some("honeybadger-delivery".to_owned())],
+            *transport.0.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_drop_dedicated_runtime_within_runtime() {
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_threads(1)
+            .with_dedicated_runtime(true)
+            .build();
+        let transport = Arc::new(ThreadTransport(Default::default()));
+        let honeybadger = Honeybadger::with_transport(config, transport).unwrap();
+        let runtime = honeybadger.runtime.as_ref().unwrap().detached();
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+        rt.block_on(async move {
+            let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+            honeybadger.enqueue(error, None).await;
+            drop(honeybadger);
+        });
+
+        // the queue worker does not keep the runtime alive
+        crate::runtime::assert_shut_down(&runtime);
+    }
+
+    #[test]
+    fn test_enqueue_across_runtimes() {
+        let config = ConfigBuilder::new("dummy-api-key").build();
+        let (honeybadger, transport) = crate::testing::capture(config);
+
+        for (delivered, message) in [(1, "first"), (2, "second")].iter() {
+            let mut rt = Runtime::new().unwrap();
+            rt.block_on(async {
+                let error = std::io::Error::new(std::io::ErrorKind::Other, *message);
+                honeybadger.enqueue(error, None).await;
+                for _ in 0..1000 {
+                    if honeybadger.queue_stats().delivered == *delivered {
+                        break;
+                    }
+                    tokio::time::delay_for(Duration::from_millis(1)).await;
+                }
+            });
+        }
+
+        // the worker dropped with the first runtime is restarted on the second runtime
+        assert_eq!(2, honeybadger.queue_stats().delivered);
+        assert_eq!(2, transport.notices().len());
+    }
+
     #[test]
     fn test_with_root() {
         let config = ConfigBuilder::new("dummy-api-key").build();
@@ -606,7 +1850,10 @@ mod tests {
     fn test_with_endpoint() {
         let config = ConfigBuilder::new("dummy-api-key").build();
 
-        assert_eq!(format!("{}{}", mockito::server_url(), HONEYBADGER_ENDPOINT), config.endpoint);
+        assert_eq!(
+            format!("{}{}", mockito::server_url(), HONEYBADGER_ENDPOINT),
+            config.endpoint
+        );
 
         let config = ConfigBuilder::new("dummy-api-key")
             .with_endpoint("http://example.com/")
@@ -642,5 +1889,73 @@ mod tests {
             .build();
 
         assert_eq!(128, config.threads);
+        assert!(!config.dedicated_runtime);
+
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_dedicated_runtime(true)
+            .build();
+
+        assert!(config.dedicated_runtime);
+    }
+
+    #[test]
+    fn test_with_source_radius() {
+        let config = ConfigBuilder::new("dummy-api-key").build();
+
+        assert_eq!(HONEYBADGER_DEFAULT_SOURCE_RADIUS, config.source_radius);
+
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_source_radius(10)
+            .build();
+
+        assert_eq!(10, config.source_radius);
+    }
+
+    #[test]
+    fn test_with_queue() {
+        let config = ConfigBuilder::new("dummy-api-key").build();
+
+        assert_eq!(HONEYBADGER_DEFAULT_QUEUE_CAPACITY, config.queue_capacity);
+        assert_eq!(OverflowPolicy::DropNewest, config.overflow_policy);
+
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_queue_capacity(5)
+            .with_overflow_policy(OverflowPolicy::Block)
+            .build();
+
+        assert_eq!(5, config.queue_capacity);
+        assert_eq!(OverflowPolicy::Block, config.overflow_policy);
+    }
+
+    #[test]
+    fn test_with_fingerprinter() {
+        let config = ConfigBuilder::new("dummy-api-key").build();
+        let honeybadger = Honeybadger::new(config).unwrap();
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "user 42 not found");
+
+        assert_eq!(
+            None,
+            honeybadger.create_notice(error, None).error.fingerprint
+        );
+
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_fingerprinter(crate::fingerprint::MessageFingerprinter)
+            .build();
+        let honeybadger = Honeybadger::new(config).unwrap();
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "user 42 not found");
+
+        assert_eq!(
+            Some("std::io::error::Error: user <n> not found".to_owned()),
+            honeybadger.create_notice(error, None).error.fingerprint
+        );
+
+        let mut error: notice::Error =
+            Box::<dyn std::error::Error>::from("user 42 not found").into();
+        error.fingerprint = Some("users".to_owned());
+
+        assert_eq!(
+            Some("users".to_owned()),
+            honeybadger.create_notice(error, None).error.fingerprint
+        );
     }
 }
@@ -6,37 +6,102 @@
 //! application errors and outages.  This library is a community-provided client for the [Honeybadger Exceptions API](https://docs.honeybadger.io/api/exceptions.html).
 //!
 //! Underneath, the client uses a [Tokio](https://tokio.rs/)-based version of
-//! [Hyper](https://hyper.rs/). Familiarity with Tokio-based systems is recommended.
+//! [Hyper](https://hyper.rs/). Familiarity with Tokio-based systems is recommended. The HTTP
+//! client may be replaced by implementing the [`Transport`](./trait.Transport.html) trait.
+//!
+//! # Runtime compatibility
+//!
+//! The runtime used to spawn tasks, wait on timers and retries is selected by a cargo feature:
+//!
+//!  - `tokio-runtime`, enabled by default, which also provides the Hyper transport used by
+//!    [`Honeybadger::new`](./struct.Honeybadger.html#method.new), the dedicated delivery runtime
+//!    and the blocking client.
+//!
+//!  - `async-std-runtime`, for applications running on [async-std](https://async.rs/).
+//!
+//!  - `smol-runtime`, for applications running on [smol](https://github.com/smol-rs/smol).
+//!
+//! With the `async-std-runtime` or `smol-runtime` features, `Honeybadger::new` sends notices with
+//! the [`IsahcTransport`](./struct.IsahcTransport.html), whose requests are driven by a
+//! background thread of the Isahc client, and may be awaited on any runtime.
+//!
+//! Cargo features are additive, so a single runtime is used when several runtime features are
+//! enabled: `tokio-runtime` takes precedence over `async-std-runtime`, which takes precedence
+//! over `smol-runtime`. Since `tokio-runtime` is a default feature, applications running on
+//! async-std or smol disable the default features:
+//!
+//! ```toml
+//! [dependencies]
+//! honeybadger = { version = "0.3", default-features = false, features = ["smol-runtime"] }
+//! ```
 //!
 //! # Error library compatibility
 //!
 //! The library provides convenience conversion traits and methods to generate a Honeybadger payload for use in the [`Honeybadger::notify`](https://docs.rs/honeybadger/0.1.3/honeybadger/struct.Honeybadger.html#method.notify) API endpoint, based on popular error Rust libraries.
 //!
 //!  - a [From](https://doc.rust-lang.org/std/convert/trait.From.html) conversion trait enables use of a `failure::Error`, if using the
-//! [failure](https://rust-lang-nursery.github.io/failure/) crate.
+//! [failure](https://rust-lang-nursery.github.io/failure/) crate, with the `failure` feature.
 //!
 //!  - the
 //!  [`notice::Error::new`](./notice/struct.Error.html#method.new) convenience method creates a `notice::Error` Honeybadger
-//!  payload, if using the [error_chain](https://docs.rs/error-chain/0.12.0/error_chain/) crate.
-//!
-//!  - alternatively, a [From](https://doc.rust-lang.org/std/convert/trait.From.html) trait allows use of a simple `Box<std::error::Error>`, if using errors from the Rust standard library.
-//!
-//! Backtraces are only supported in the ErrorChain and Failure crates.
+//!  payload, if using the [error_chain](https://docs.rs/error-chain/0.12.0/error_chain/) crate,
+//!    with the `error-chain` feature.
+//!
+//!  - a [From](https://doc.rust-lang.org/std/convert/trait.From.html) conversion trait enables use
+//!    of an `anyhow::Error` or an `eyre::Report`, with the `anyhow` or `eyre` features. The
+//!    backtrace of an eyre report is recorded by the handler installed with
+//!    [`eyre_handler::install`](./eyre_handler/fn.install.html).
+//!
+//!  - errors of the Rust standard library are accepted through the
+//!    [`IntoNotice`](./trait.IntoNotice.html) trait, which domain errors may also implement to
+//!    enrich their notices with tags, a fingerprint or context. Other types implementing
+//!    `std::error::Error` are wrapped in a [`notice::StdError`](./notice/struct.StdError.html). A
+//!    [From](https://doc.rust-lang.org/std/convert/trait.From.html) trait allows use of a simple
+//!    `Box<std::error::Error>`. The `source` chain of the error is reported as its causes.
+//!
+//!  - with the `derive` feature, `#[derive(HoneybadgerNotice)]` implements `IntoNotice` for a
+//!    domain error, which may also implement `std::error::Error`, from `#[honeybadger(...)]`
+//!    attributes setting the class, fingerprint, tags and context of its notices, see the
+//!    [honeybadger-derive](https://docs.rs/honeybadger-derive) crate.
+//!
+//! Errors converted with the `From` trait are passed to `Honeybadger::notify` as a
+//! `notice::Error`, which implements `IntoNotice`.
+//!
+//! Notices are grouped by the Rust type of the error: the class of a notice is the type name of
+//! the error, or the path of the `ErrorKind` variant of an ErrorChain error, while its message
+//! holds the display text of the error.
+//! The grouping may be overridden with a fingerprint, set by the error itself through
+//! `IntoNotice`, or computed by the [`Fingerprinter`](./fingerprint/trait.Fingerprinter.html)
+//! configured with
+//! [`ConfigBuilder::with_fingerprinter`](./struct.ConfigBuilder.html#method.with_fingerprinter).
+//!
+//! Errors of this crate, returned when a notice could not be delivered, are described by the
+//! [`errors::Error`](./errors/enum.Error.html) enum, which exposes the HTTP status and body of
+//! the response of the Honeybadger API, and whether the delivery may be retried.
+//!
+//! Backtraces held by the ErrorChain and Failure crates are forwarded to Honeybadger, as well as
+//! the `std::backtrace::Backtrace` provided by standard library errors when the
+//! `error-generic-member-access` feature is enabled on a nightly toolchain. Errors without a
+//! backtrace of their own are reported with the backtrace captured at notify time.
+//!
+//! Applications without a Tokio runtime may use the blocking client in
+//! [`blocking::Honeybadger`](./blocking/struct.Honeybadger.html), which owns a dedicated runtime.
+//!
+//! Panics can be reported as well, by installing the hook in
+//! [`panic::install`](./panic/fn.install.html).
+//!
+//! Error reporting can be tested without the Honeybadger API, using the in-memory transport of
+//! the [`testing`](./testing/index.html) module.
 //!
 //! # Example
 //!
-//! Assuming the project is setup to use
-//! [ErrorChain](http://brson.github.io/2016/11/30/starting-with-error-chain), the following
-//! example will execute code in `do_work`, send a honeybadger exception if it fails, and
-//! subsequently end the program.  
+//! The following example will execute code in `do_work`, send a honeybadger exception if it
+//! fails, and subsequently end the program.
 //!
 //! ```rust, no_run
-//! # #[macro_use] extern crate error_chain;
-//! # error_chain! {
-//! # }
 //! use tokio::runtime::Runtime;
 //!
-//! async fn do_work() -> Result<()> {
+//! async fn do_work() -> std::io::Result<()> {
 //!
 //!   // write code ...
 //!
@@ -54,7 +119,7 @@
 //! let future = async move {
 //!   match do_work().await {
 //!     Ok(_) => Ok(()),
-//!     Err(e) => hb.notify(honeybadger::notice::Error::new(&e), None).await
+//!     Err(e) => hb.notify(e, None).await
 //!   }
 //! };
 //!
@@ -65,20 +130,57 @@
 //!
 //! Please check the examples folder for further alternatives.
 //!
-//
-// Increase the compiler's recursion limit for the `error_chain` crate.
-#![recursion_limit = "1024"]
+//! For local development and CI, the `honeybadger-fake-server` binary serves a fake Honeybadger
+//! API, which writes the notices it receives as JSON lines and can reply with errors on demand.
+//!
+#![cfg_attr(error_generic_member_access, feature(error_generic_member_access))]
 
-#[macro_use]
-extern crate error_chain;
 #[macro_use]
 extern crate log;
 #[macro_use]
 extern crate serde_derive;
 
+#[cfg(feature = "tokio-runtime")]
+pub mod blocking;
+mod breaker;
 pub mod errors;
+mod executor;
+#[cfg(feature = "eyre")]
+pub mod eyre_handler;
+pub mod fingerprint;
 mod honeybadger;
 pub mod notice;
+pub mod panic;
+mod queue;
+#[cfg(feature = "tokio-runtime")]
+mod runtime;
+mod source;
+mod spool;
+pub mod testing;
+mod transport;
 
 // export
-pub use crate::honeybadger::{ConfigBuilder, Honeybadger};
+pub use crate::breaker::{CircuitState, CircuitStats};
+pub use crate::fingerprint::Fingerprinter;
+pub use crate::honeybadger::{ConfigBuilder, FlushGuard, Honeybadger};
+pub use crate::notice::IntoNotice;
+pub use crate::queue::{FlushStats, OverflowPolicy, QueueStats};
+#[cfg(feature = "tokio-runtime")]
+pub use crate::transport::HyperTransport;
+#[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+pub use crate::transport::IsahcTransport;
+pub use crate::transport::{Transport, TransportFuture};
+#[cfg(feature = "derive")]
+pub use honeybadger_derive::HoneybadgerNotice;
+
+/// Dependencies of the code generated by `#[derive(HoneybadgerNotice)]`.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json;
+
+    /// The causes of an error deriving `HoneybadgerNotice` with `#[honeybadger(source)]`.
+    pub fn causes(error: &(dyn std::error::Error + 'static)) -> Option<Vec<crate::notice::Error>> {
+        crate::notice::Error::std_causes(error)
+    }
+}
@@ -1,140 +1,905 @@
 //! Data structures for marshaling to honeybadger's API
+use backtrace::Backtrace;
+#[cfg(feature = "error-chain")]
 use error_chain::ChainedError;
-use failure;
+use serde_json::Value;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::From;
+#[cfg(feature = "error-chain")]
+use std::fmt;
+#[cfg(feature = "error-chain")]
+use std::iter;
+use std::path::Path;
+
+pub(crate) const PROJECT_ROOT: &str = "[PROJECT_ROOT]";
+const CRATES_ROOT: &str = "[CRATES]";
+const CARGO_REGISTRY: &str = "/.cargo/registry/src/";
+
+/// Class of the errors hidden behind a trait object, whose concrete type is unknown.
+const STD_ERROR_CLASS: &str = "std::error::Error";
+#[cfg(feature = "failure")]
+const FAILURE_CLASS: &str = "failure::Error";
+#[cfg(feature = "anyhow")]
+const ANYHOW_CLASS: &str = "anyhow::Error";
+#[cfg(feature = "anyhow")]
+const ANYHOW_CONTEXT_CLASS: &str = "anyhow::Context";
+#[cfg(feature = "eyre")]
+const EYRE_CLASS: &str = "eyre::Report";
+#[cfg(feature = "eyre")]
+const EYRE_CONTEXT_CLASS: &str = "eyre::Context";
+
+/// Maximum number of causes walked in the `source` chain of a standard library error, which
+/// guards against cyclic or unbounded chains.
+const MAX_CAUSES: usize = 32;
+
+/// Function prefixes belonging to the machinery that captures a backtrace, which are trimmed
+/// from the top of each backtrace.
+const CAPTURE_PREFIXES: &[&str] = &[
+    "backtrace::",
+    "error_chain::",
+    "failure::",
+    "anyhow::",
+    "eyre::",
+    "std::backtrace",
+];
+
+/// Function prefixes belonging to the Rust runtime and common executors, which are never
+/// considered to be application code.
+const RUNTIME_PREFIXES: &[&str] = &[
+    "std::",
+    "core::",
+    "alloc::",
+    "tokio::",
+    "futures::",
+    "__rust",
+    "_start",
+    "__libc",
+];
 
 /// Serializable root notice event, for use with the notify endpoint of the Honeybadger API.
-#[derive(Serialize)]
-pub struct Notice<'req> {
-    pub api_key: &'req str,
+///
+/// A `Notice` owns all of its data, so it may be queued, sent across tasks, persisted and
+/// replayed at a later time.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Notice {
+    pub api_key: String,
     pub notifier: Notifier,
     pub error: Error,
-    pub request: Request<'req>,
-    pub server: Server<'req>,
+    pub request: Request,
+    pub server: Server,
 }
 
 /// Serializable leaf node representing the error to notify on.
-#[derive(Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Error {
     pub class: String,
     pub message: Option<String>,
     pub causes: Option<Vec<Error>>,
+    pub backtrace: Option<Vec<Frame>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Serializable leaf node representing a single line of a backtrace.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Frame {
+    pub number: String,
+    pub file: String,
+    pub method: String,
+    pub column: Option<String>,
+    pub context: Option<FrameContext>,
+    pub source: Option<BTreeMap<usize, String>>,
+}
+
+/// Whether a backtrace frame belongs to the project, or to a dependency or the Rust runtime.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrameContext {
+    App,
+    All,
+}
+
+impl Frame {
+    /// Convert a resolved `backtrace::Backtrace` into the list of frames expected by the
+    /// Honeybadger Exceptions API. Inlined functions are reported as separate frames.
+    pub fn from_backtrace(backtrace: &Backtrace) -> Vec<Frame> {
+        let mut frames = Vec::new();
+        for frame in backtrace.frames() {
+            for symbol in frame.symbols() {
+                frames.push(Frame {
+                    number: symbol.lineno().map(|n| n.to_string()).unwrap_or_default(),
+                    file: symbol
+                        .filename()
+                        .map(|f| f.display().to_string())
+                        .unwrap_or_default(),
+                    method: symbol
+                        .name()
+                        .map(|n| format!("{:#}", n))
+                        .unwrap_or_else(|| "<unknown>".to_owned()),
+                    column: symbol.colno().map(|c| c.to_string()),
+                    context: None,
+                    source: None,
+                });
+            }
+        }
+        frames
+    }
+
+    /// Parse the textual representation of a backtrace, as printed by the `Debug` trait of
+    /// `backtrace::Backtrace`. Opaque wrappers like `failure::Backtrace` only expose this form.
+    pub fn from_backtrace_str(backtrace: &str) -> Vec<Frame> {
+        let mut frames: Vec<Frame> = Vec::new();
+        for line in backtrace.lines().map(str::trim) {
+            if let Some(location) = line.strip_prefix("at ") {
+                if let Some(frame) = frames.last_mut() {
+                    let (file, number, column) = Frame::parse_location(location);
+                    frame.file = file.to_owned();
+                    frame.number = number.to_owned();
+                    frame.column = column.map(|c| c.to_owned());
+                }
+            } else if let Some((index, method)) = line.split_once(": ") {
+                if index.parse::<u32>().is_err() {
+                    continue;
+                }
+                let method = match method.split_once(" - ") {
+                    Some((address, name)) if address.starts_with("0x") => name,
+                    _ => method,
+                };
+                frames.push(Frame {
+                    number: String::new(),
+                    file: String::new(),
+                    method: method.to_owned(),
+                    column: None,
+                    context: None,
+                    source: None,
+                });
+            }
+        }
+        frames
+    }
+
+    /// Normalize a list of frames against the project root:
+    ///
+    ///   - frames from the backtrace capture and from this crate are removed,
+    ///   - files under `project_root` (or relative to it) are rewritten to `[PROJECT_ROOT]/...`
+    ///     and marked with the `app` context,
+    ///   - files from the cargo registry are rewritten to `[CRATES]/...`,
+    ///   - every other frame, including the Rust runtime, is marked with the `all` context.
+    pub fn normalize(frames: Vec<Frame>, project_root: &str) -> Vec<Frame> {
+        let root = project_root.trim_end_matches('/');
+        frames
+            .into_iter()
+            .skip_while(|frame| frame.has_prefix(CAPTURE_PREFIXES))
+            .filter(|frame| !frame.has_prefix(&["honeybadger::"]))
+            .map(|mut frame| {
+                let app = if let Some(relative) = Frame::strip_root(&frame.file, root) {
+                    frame.file = format!("{}/{}", PROJECT_ROOT, relative);
+                    !frame.has_prefix(RUNTIME_PREFIXES)
+                } else {
+                    if let Some(index) = frame.file.find(CARGO_REGISTRY) {
+                        let registry = &frame.file[index + CARGO_REGISTRY.len()..];
+                        let relative = registry.split_once('/').map_or(registry, |(_, r)| r);
+                        frame.file = format!("{}/{}", CRATES_ROOT, relative);
+                    }
+                    false
+                };
+                frame.context = Some(if app {
+                    FrameContext::App
+                } else {
+                    FrameContext::All
+                });
+                frame
+            })
+            .collect()
+    }
+
+    fn has_prefix(&self, prefixes: &[&str]) -> bool {
+        let method = self.method.trim_start_matches('<');
+        prefixes.iter().any(|prefix| method.starts_with(prefix))
+    }
+
+    /// Return the path relative to the project root, if the file lives in the project. Relative
+    /// paths are emitted by rustc for the crate being built, and are assumed to be in the project.
+    fn strip_root<'a>(file: &'a str, root: &str) -> Option<&'a str> {
+        if file.is_empty() || file.starts_with('<') {
+            None
+        } else if Path::new(file).is_relative() {
+            Some(file)
+        } else if root.is_empty() {
+            None
+        } else {
+            file.strip_prefix(root)
+                .and_then(|relative| relative.strip_prefix('/'))
+        }
+    }
+
+    /// Split a `file:line:column` location, where both the line and column are optional.
+    fn parse_location(location: &str) -> (&str, &str, Option<&str>) {
+        let (rest, last) = match location.rsplit_once(':') {
+            Some((rest, last)) if last.parse::<u32>().is_ok() => (rest, last),
+            _ => return (location, "", None),
+        };
+        match rest.rsplit_once(':') {
+            Some((file, line)) if line.parse::<u32>().is_ok() => (file, line, Some(last)),
+            _ => (rest, last, None),
+        }
+    }
+}
+
+/// Invoke a macro with the common error types of the standard library and of the crates the
+/// notices are built with, appended to its arguments. These types are named behind a trait
+/// object, see `known_type_name`, and implement `IntoNotice`, see `std_into_notice`.
+macro_rules! known_error_types {
+    ($callback:ident!($($args:tt)*)) => {
+        $callback! {
+            $($args)*
+            std::io::Error,
+            std::fmt::Error,
+            std::num::ParseIntError,
+            std::num::ParseFloatError,
+            std::num::TryFromIntError,
+            std::str::ParseBoolError,
+            std::str::Utf8Error,
+            std::string::FromUtf8Error,
+            std::char::ParseCharError,
+            std::net::AddrParseError,
+            std::env::VarError,
+            std::ffi::NulError,
+            std::time::SystemTimeError,
+            serde_json::Error,
+            url::ParseError,
+            http::Error
+        }
+    };
+}
+
+/// Name the concrete type of an error behind a trait object, among the known error types, see
+/// `known_error_types`.
+macro_rules! known_type_name {
+    ($error:expr) => {
+        known_error_types!(known_type_name!($error,))
+    };
+    ($error:expr, $($ty:ty),*) => {
+        None $(.or_else(|| $error.downcast_ref::<$ty>().map(|_| std::any::type_name::<$ty>())))*
+    };
 }
 
 /// Implementation of the `From` trait for `failure::Error`, which allows bastic failure
 /// functionality to be used with the `Honeybadger::into_payload` API, to marshal a payload for
-/// Honeybadger's Exceptions API.
+/// Honeybadger's Exceptions API. Available with the `failure` feature.
+///
+/// The class is the name of the failure, see `Fail::name`, and the message its display text.
+///
+/// # Example
+///
+/// ```rust, no_run
+/// #[macro_use] extern crate failure;
+/// #[derive(Fail, Debug)]
+/// #[fail(display = "Failure error")]
+/// struct MyCustomError;
+/// # fn main() {
+/// # use honeybadger::{ConfigBuilder, Honeybadger};
+/// # use tokio::runtime::Runtime;
+/// # let api_token = "ffffff";
+/// # let config = ConfigBuilder::new(api_token).build();
+/// # let mut honeybadger = Honeybadger::new(config).unwrap();
+///
+/// let error: Result<(), failure::Error> = Err(MyCustomError {}.into());
+///
+/// let mut rt = Runtime::new().unwrap();
+/// let future = honeybadger.notify(
+///   honeybadger::notice::Error::from(error.unwrap_err()),
+///   None);
+///
+/// rt.block_on(future).unwrap();
+/// #
+/// # }
+/// ```
+#[cfg(feature = "failure")]
 impl From<failure::Error> for Error {
     fn from(error: failure::Error) -> Error {
-        Error {
-            class: format!("{}", error),
-            message: Some(format!("{:?}", error)),
-            causes: Some(
-                error
-                    .iter_causes()
-                    .map(|cause| Error {
-                        class: format!("{}", cause),
-                        message: Some(format!("{:?}", cause)),
-                        causes: None,
-                    })
-                    .collect(),
-            ),
-        }
+        Error::from(&error)
     }
 }
 
+#[cfg(feature = "failure")]
 impl From<&failure::Error> for Error {
     fn from(error: &failure::Error) -> Error {
         Error {
-            class: format!("{}", error),
-            message: Some(format!("{:?}", error)),
+            class: error
+                .name()
+                .or_else(|| known_type_name!(error))
+                .unwrap_or(FAILURE_CLASS)
+                .to_owned(),
+            message: Some(format!("{}", error)),
             causes: Some(
                 error
                     .iter_causes()
                     .map(|cause| Error {
-                        class: format!("{}", cause),
-                        message: Some(format!("{:?}", cause)),
+                        class: cause
+                            .name()
+                            .or_else(|| known_type_name!(cause))
+                            .unwrap_or(FAILURE_CLASS)
+                            .to_owned(),
+                        message: Some(format!("{}", cause)),
                         causes: None,
+                        backtrace: None,
+                        fingerprint: None,
+                        tags: Vec::new(),
                     })
                     .collect(),
             ),
+            backtrace: Some(Frame::from_backtrace_str(&error.backtrace().to_string()))
+                .filter(|frames| !frames.is_empty()),
+            fingerprint: None,
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// Implementation of the `From` trait for standard library errors. The `source` chain of the
+/// error is flattened into its causes, and a `std::backtrace::Backtrace` provided by the error is
+/// forwarded when the `error-generic-member-access` feature is enabled on a nightly toolchain.
+///
+/// The class is the type name of common errors, and `std::error::Error` for other types hidden
+/// behind the trait object, while the message is the display text of the error. An error whose
+/// concrete type is known is wrapped in a [`StdError`](struct.StdError.html) instead, to be
+/// reported with its type name.
+impl From<&(dyn std::error::Error + 'static)> for Error {
+    fn from(error: &(dyn std::error::Error + 'static)) -> Error {
+        Error {
+            causes: Error::std_causes(error),
+            backtrace: Error::std_backtrace(error),
+            ..Error::std_err(error)
+        }
+    }
+}
+
+/// Implementation of the `From` trait for `anyhow::Error`, available with the `anyhow` feature.
+///
+/// The class is the type name of the root cause, or `anyhow::Error` if its type cannot be named,
+/// the message the display text of the outermost context, and the causes the rest of the context
+/// chain, whose contexts are classed `anyhow::Context`. The backtrace captured by anyhow is
+/// forwarded, when backtraces are enabled with `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`.
+#[cfg(feature = "anyhow")]
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Error {
+        Error::from(&error)
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl From<&anyhow::Error> for Error {
+    fn from(error: &anyhow::Error) -> Error {
+        let root = error.root_cause();
+        let class = known_type_name!(root).unwrap_or(ANYHOW_CLASS);
+        Error {
+            class: class.to_owned(),
+            message: Some(format!("{}", error)),
+            causes: Error::context_chain(error.chain(), (root, class), ANYHOW_CONTEXT_CLASS),
+            backtrace: Error::captured_backtrace(error.backtrace()),
+            fingerprint: None,
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// Implementation of the `From` trait for `eyre::Report`, available with the `eyre` feature.
+///
+/// The class is the type name of the root cause, or `eyre::Report` if its type cannot be named,
+/// the message the display text of the outermost context, and the causes the rest of the context
+/// chain, whose contexts are classed `eyre::Context`. The backtrace is forwarded if the report was
+/// created with the handler installed by
+/// [`eyre_handler::install`](../eyre_handler/fn.install.html).
+#[cfg(feature = "eyre")]
+impl From<eyre::Report> for Error {
+    fn from(report: eyre::Report) -> Error {
+        Error::from(&report)
+    }
+}
+
+#[cfg(feature = "eyre")]
+impl From<&eyre::Report> for Error {
+    fn from(report: &eyre::Report) -> Error {
+        let root = report.root_cause();
+        let class = known_type_name!(root).unwrap_or(EYRE_CLASS);
+        Error {
+            class: class.to_owned(),
+            message: Some(format!("{}", report)),
+            causes: Error::context_chain(report.chain(), (root, class), EYRE_CONTEXT_CLASS),
+            backtrace: report
+                .handler()
+                .downcast_ref::<crate::eyre_handler::Handler>()
+                .map(|handler| handler.frames())
+                .filter(|frames| !frames.is_empty()),
+            fingerprint: None,
+            tags: Vec::new(),
         }
     }
 }
 
 impl From<Box<dyn std::error::Error>> for Error {
     fn from(error: Box<dyn std::error::Error>) -> Error {
+        Error::from(&*error)
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for Error {
+    fn from(error: Box<dyn std::error::Error + Send + Sync>) -> Error {
+        Error::from(&*error as &(dyn std::error::Error + 'static))
+    }
+}
+
+/// Conversion of an error into the `error` node of a notice, along with the context sent with it.
+///
+/// [`Honeybadger::notify`](../struct.Honeybadger.html#method.notify) accepts any type
+/// implementing this trait. It is implemented for the common errors of the standard library, such
+/// as `std::io::Error`: the class is the type name of the error, the message its display text, and
+/// the causes its `source` chain. `failure`, ErrorChain, anyhow and eyre errors, as well as boxed
+/// trait objects, are first converted into a `notice::Error` using the `From` trait.
+///
+/// The trait is not implemented for every type implementing `std::error::Error`, so that a domain
+/// error may implement it itself: third-party and domain errors which do not implement this trait
+/// are not accepted by `Honeybadger::notify` as they are, and are wrapped in a
+/// [`StdError`](struct.StdError.html) instead, e.g. `notify(notice::StdError(error), None)`.
+///
+/// Every method has a default implementation, so a domain error, whether it implements
+/// `std::error::Error` or not, only overrides the details it knows about.
+///
+/// # Example
+///
+/// ```rust
+/// # use honeybadger::notice::{self, IntoNotice};
+/// # use serde_json::Value;
+/// # use std::collections::HashMap;
+/// enum PaymentError {
+///     Declined { order_id: u64 },
+/// }
+///
+/// impl IntoNotice for PaymentError {
+///     fn message(&self) -> Option<String> {
+///         Some("payment declined".to_owned())
+///     }
+///
+///     fn tags(&self) -> Vec<String> {
+///         vec!["payments".to_owned()]
+///     }
+///
+///     fn context(&self) -> HashMap<String, Value> {
+///         match self {
+///             PaymentError::Declined { order_id } => {
+///                 vec![("order_id".to_owned(), Value::from(*order_id))].into_iter().collect()
+///             }
+///         }
+///     }
+/// }
+///
+/// let error = PaymentError::Declined { order_id: 42 }.into_notice_error();
+/// assert_eq!(vec!["payments"], error.tags);
+/// ```
+pub trait IntoNotice {
+    /// The class of the error, which groups notices in Honeybadger. Defaults to the type name.
+    fn class(&self) -> String {
+        std::any::type_name::<Self>().to_owned()
+    }
+
+    /// The message of the error.
+    fn message(&self) -> Option<String> {
+        None
+    }
+
+    /// The errors which caused this error, from the closest to the root cause.
+    fn causes(&self) -> Option<Vec<Error>> {
+        None
+    }
+
+    /// The backtrace of the error. A backtrace is captured when the notice is created, if the
+    /// error does not carry one.
+    fn backtrace(&self) -> Option<Vec<Frame>> {
+        None
+    }
+
+    /// The fingerprint used by Honeybadger to group notices, in place of the class and location.
+    fn fingerprint(&self) -> Option<String> {
+        None
+    }
+
+    /// The tags of the notice.
+    fn tags(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// The context of the notice. Context passed to `Honeybadger::notify` takes precedence over
+    /// the context of the error.
+    fn context(&self) -> HashMap<String, Value> {
+        HashMap::new()
+    }
+
+    /// Whether the error is expected, and not reported by `Honeybadger::notify` and
+    /// `Honeybadger::enqueue`.
+    fn is_ignored(&self) -> bool {
+        false
+    }
+
+    /// Consumes the error and returns the `error` node of the notice.
+    fn into_notice_error(self) -> Error
+    where
+        Self: Sized,
+    {
         Error {
-            class: format!("{}", error),
-            message: Some(format!("{:?}", error)),
-            causes: None,
+            class: self.class(),
+            message: self.message(),
+            causes: self.causes(),
+            backtrace: self.backtrace(),
+            fingerprint: self.fingerprint(),
+            tags: self.tags(),
         }
     }
 }
 
+/// Implement `IntoNotice` for the known error types, see `known_error_types`. The class is the type name of the error, the
+/// message its display text, and the causes its `source` chain.
+macro_rules! std_into_notice {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoNotice for $ty {
+                fn message(&self) -> Option<String> {
+                    Some(self.to_string())
+                }
+
+                fn causes(&self) -> Option<Vec<Error>> {
+                    Error::std_causes(self)
+                }
+
+                fn backtrace(&self) -> Option<Vec<Frame>> {
+                    Error::std_backtrace(self)
+                }
+            }
+        )*
+    };
+}
+
+known_error_types!(std_into_notice!());
+
+/// Wrapper converting any type implementing `std::error::Error` into a notice, for errors which
+/// do not implement [`IntoNotice`](trait.IntoNotice.html) themselves.
+///
+/// The class is the type name of the wrapped error, the message its display text, and the causes
+/// its `source` chain.
+///
+/// # Example
+///
+/// ```rust
+/// # use honeybadger::notice::{self, IntoNotice};
+/// #[derive(Debug)]
+/// struct ConfigError;
+///
+/// impl std::fmt::Display for ConfigError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+///         write!(f, "invalid configuration")
+///     }
+/// }
+///
+/// impl std::error::Error for ConfigError {}
+///
+/// let error = notice::StdError(ConfigError).into_notice_error();
+/// assert!(error.class.ends_with("ConfigError"));
+/// assert_eq!(Some("invalid configuration".to_owned()), error.message);
+/// ```
+#[derive(Debug)]
+pub struct StdError<E>(pub E);
+
+impl<E: std::error::Error + 'static> IntoNotice for StdError<E> {
+    fn class(&self) -> String {
+        std::any::type_name::<E>().to_owned()
+    }
+
+    fn message(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+
+    fn causes(&self) -> Option<Vec<Error>> {
+        Error::std_causes(&self.0)
+    }
+
+    fn backtrace(&self) -> Option<Vec<Frame>> {
+        Error::std_backtrace(&self.0)
+    }
+}
+
+impl IntoNotice for Error {
+    fn class(&self) -> String {
+        self.class.clone()
+    }
+
+    fn message(&self) -> Option<String> {
+        self.message.clone()
+    }
+
+    fn causes(&self) -> Option<Vec<Error>> {
+        self.causes.clone()
+    }
+
+    fn backtrace(&self) -> Option<Vec<Frame>> {
+        self.backtrace.clone()
+    }
+
+    fn fingerprint(&self) -> Option<String> {
+        self.fingerprint.clone()
+    }
+
+    fn tags(&self) -> Vec<String> {
+        self.tags.clone()
+    }
+
+    fn into_notice_error(self) -> Error {
+        self
+    }
+}
+
 impl Error {
-    /// Internal API to create a new Error instance for serialization purposes.
+    /// Internal API to create a new Error instance for serialization purposes, from an
+    /// ErrorChain error. Available with the `error-chain` feature.
+    ///
+    /// The class is the path of the `ErrorKind` variant, for example
+    /// `my_app::errors::ErrorKind::Msg`, and the message the display text of the error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #[macro_use] extern crate error_chain;
+    /// error_chain! {
+    ///   errors {
+    ///     MyCustomError
+    ///   }
+    /// }
+    /// #
+    /// # fn main() {
+    /// # use honeybadger::{ConfigBuilder, Honeybadger};
+    /// # use tokio::runtime::Runtime;
+    /// # let api_token = "ffffff";
+    /// # let config = ConfigBuilder::new(api_token).build();
+    /// # let mut honeybadger = Honeybadger::new(config).unwrap();
+    ///
+    /// let error : Result<()> = Err(ErrorKind::MyCustomError.into());
+    ///
+    /// let mut rt = Runtime::new().unwrap();
+    /// let future = honeybadger.notify(
+    ///   honeybadger::notice::Error::new(&error.unwrap_err()),
+    ///   None);
+    ///
+    /// rt.block_on(future);
+    /// #
+    /// # }
+    /// ```
+    #[cfg(feature = "error-chain")]
     pub fn new<E>(error: &E) -> Error
     where
         E: ChainedError,
+        E::ErrorKind: fmt::Debug,
     {
+        let class = Error::kind_class(error.kind());
+        let top = Error {
+            class: class.clone(),
+            ..Error::std_err(error)
+        };
         Error {
-            class: error.description().to_string(),
-            message: Some(error.display_chain().to_string()),
-            causes: Some(error.iter().map(|cause| Error::std_err(cause)).collect()),
+            class,
+            message: Some(error.to_string()),
+            causes: Some(iter::once(top).chain(Error::source_chain(error)).collect()),
+            backtrace: error.backtrace().map(Frame::from_backtrace),
+            fingerprint: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Normalize the backtraces of this error and its causes against the project root, see
+    /// [`Frame::normalize`](struct.Frame.html#method.normalize).
+    pub fn normalize_backtrace(&mut self, project_root: &str) {
+        if let Some(frames) = self.backtrace.take() {
+            self.backtrace = Some(Frame::normalize(frames, project_root));
+        }
+        for cause in self.causes.iter_mut().flatten() {
+            cause.normalize_backtrace(project_root);
         }
     }
 
-    fn std_err(error: &dyn std::error::Error) -> Error {
+    /// A single error of a cause chain, whose own sources are reported alongside it.
+    fn std_err(error: &(dyn std::error::Error + 'static)) -> Error {
         Error {
-            class: error.description().to_string(),
-            message: None,
-            causes: error.source().map(|cause| vec![Error::std_err(cause)]),
+            class: known_type_name!(error)
+                .unwrap_or(STD_ERROR_CLASS)
+                .to_owned(),
+            message: Some(format!("{}", error)),
+            causes: None,
+            backtrace: None,
+            fingerprint: None,
+            tags: Vec::new(),
         }
     }
+
+    /// The path of an `ErrorKind` variant, from the type name of the kind and the name of the
+    /// variant printed first by its derived `Debug` implementation.
+    #[cfg(feature = "error-chain")]
+    fn kind_class<K: fmt::Debug>(kind: &K) -> String {
+        let debug = format!("{:?}", kind);
+        let variant = debug
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or_default();
+        format!("{}::{}", std::any::type_name::<K>(), variant)
+    }
+
+    /// The sources of an error, or `None` if it has no source.
+    pub(crate) fn std_causes(error: &(dyn std::error::Error + 'static)) -> Option<Vec<Error>> {
+        Some(Error::source_chain(error)).filter(|causes| !causes.is_empty())
+    }
+
+    /// Walk the `source` chain of an error, stopping at `MAX_CAUSES` or at a source that was
+    /// already visited.
+    fn source_chain(error: &(dyn std::error::Error + 'static)) -> Vec<Error> {
+        let mut visited = vec![error];
+        let mut causes = Vec::new();
+        let mut source = error.source();
+        while let Some(cause) = source {
+            if causes.len() >= MAX_CAUSES || visited.iter().any(|seen| std::ptr::eq(*seen, cause)) {
+                break;
+            }
+            visited.push(cause);
+            causes.push(Error {
+                backtrace: Error::std_backtrace(cause),
+                ..Error::std_err(cause)
+            });
+            source = cause.source();
+        }
+        causes
+    }
+
+    #[cfg(error_generic_member_access)]
+    fn std_backtrace(error: &(dyn std::error::Error + 'static)) -> Option<Vec<Frame>> {
+        std::error::request_ref::<std::backtrace::Backtrace>(error)
+            .and_then(Error::captured_backtrace)
+    }
+
+    #[cfg(not(error_generic_member_access))]
+    fn std_backtrace(_error: &(dyn std::error::Error + 'static)) -> Option<Vec<Frame>> {
+        None
+    }
+
+    /// The frames of a `std::backtrace::Backtrace`, if it was captured.
+    #[cfg(any(feature = "anyhow", error_generic_member_access))]
+    fn captured_backtrace(backtrace: &std::backtrace::Backtrace) -> Option<Vec<Frame>> {
+        Some(backtrace)
+            .filter(|backtrace| backtrace.status() == std::backtrace::BacktraceStatus::Captured)
+            .map(|backtrace| Frame::from_backtrace_str(&backtrace.to_string()))
+            .filter(|frames| !frames.is_empty())
+    }
+
+    /// Flatten the chain of an `anyhow::Error` or `eyre::Report`, whose first error is the
+    /// outermost context, into the causes of the notice. The root cause is classed like the
+    /// notice, and the layers above it whose type cannot be named, i.e. the contexts, are classed
+    /// `context_class`.
+    #[cfg(any(feature = "anyhow", feature = "eyre"))]
+    fn context_chain<'a, I>(
+        chain: I,
+        (root, root_class): (&(dyn std::error::Error + 'static), &str),
+        context_class: &str,
+    ) -> Option<Vec<Error>>
+    where
+        I: Iterator<Item = &'a (dyn std::error::Error + 'static)>,
+    {
+        let causes = chain.skip(1).take(MAX_CAUSES).map(|error| {
+            let class = if std::ptr::addr_eq(error, root) {
+                root_class
+            } else {
+                known_type_name!(error).unwrap_or(context_class)
+            };
+            Error {
+                class: class.to_owned(),
+                ..Error::std_err(error)
+            }
+        });
+        Some(causes.collect()).filter(|causes: &Vec<Error>| !causes.is_empty())
+    }
 }
 
 /// Serializable leaf node representing the meta details on this crate
-#[derive(Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Notifier {
-    pub name: &'static str,
-    pub url: &'static str,
-    pub version: &'static str,
+    pub name: String,
+    pub url: String,
+    pub version: String,
 }
 
 /// Leaf node containing the context hash and environment at the time of
 /// serialization.
-#[derive(Serialize)]
-pub struct Request<'req> {
-    pub context: Option<HashMap<&'req str, &'req str>>,
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Request {
+    pub context: Option<HashMap<String, Value>>,
     pub cgi_data: HashMap<String, String>,
 }
 
 /// Leaf node containing OS system information at the time of serialization
-#[derive(Serialize)]
-pub struct Server<'req> {
-    pub project_root: &'req str,
-    pub environment_name: &'req str,
-    pub hostname: &'req str,
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Server {
+    pub project_root: String,
+    pub environment_name: String,
+    pub hostname: String,
     pub time: u64,
     pub pid: u32,
 }
 
+/// Minimal notice of the given error class, for the tests of the delivery path.
+#[cfg(test)]
+pub(crate) fn test_notice(class: &str) -> Notice {
+    Notice {
+        api_key: "dummy-api-key".to_owned(),
+        notifier: Notifier {
+            name: "honeybadger".to_owned(),
+            url: "https://example.com".to_owned(),
+            version: "0.0.0".to_owned(),
+        },
+        error: Error {
+            class: class.to_owned(),
+            message: None,
+            causes: None,
+            backtrace: None,
+            fingerprint: None,
+            tags: Vec::new(),
+        },
+        request: Request {
+            context: None,
+            cgi_data: Default::default(),
+        },
+        server: Server {
+            project_root: "".to_owned(),
+            environment_name: "".to_owned(),
+            hostname: "".to_owned(),
+            time: 0,
+            pid: 0,
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use crate::errors::*;
     use crate::notice;
 
+    #[cfg(feature = "error-chain")]
+    mod chained {
+        error_chain::error_chain! {
+            errors {
+                RedirectionError {
+                    description("The endpoint replied with a redirect")
+                }
+                RateExceededError {
+                    description("Honeybadger rate limit exceeded")
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "error-chain")]
     #[test]
     fn test_chained_err() {
+        use self::chained::*;
+
         let error: Result<()> = Err(ErrorKind::RedirectionError.into());
         let chain = error.chain_err(|| ErrorKind::RateExceededError);
         let notice = notice::Error::new(&chain.unwrap_err());
 
-        assert_eq!("Honeybadger rate limit exceeded", notice.class);
+        assert_eq!(
+            "honeybadger::notice::tests::chained::ErrorKind::RateExceededError",
+            notice.class
+        );
+        assert_eq!(
+            Some("Honeybadger rate limit exceeded".to_owned()),
+            notice.message
+        );
         if let Some(causes) = notice.causes {
             assert_eq!(2, causes.len());
         } else {
@@ -142,10 +907,388 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "failure")]
     #[test]
     fn test_failure_err() {
         let error: failure::Error = failure::err_msg("test_error_message");
         let notice: notice::Error = notice::From::from(error);
-        assert_eq!("test_error_message", notice.class);
+        assert_eq!("failure::ErrorMessage", notice.class);
+        assert_eq!(Some("test_error_message".to_owned()), notice.message);
+
+        let error: failure::Error = "NaN".parse::<u8>().unwrap_err().into();
+        let notice: notice::Error = notice::From::from(error);
+        assert_eq!("core::num::error::ParseIntError", notice.class);
+    }
+
+    #[derive(Debug)]
+    struct Wrapped(&'static str, Option<Box<Wrapped>>);
+
+    impl std::fmt::Display for Wrapped {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl std::error::Error for Wrapped {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            self.1.as_ref().map(|inner| inner.as_ref() as _)
+        }
+    }
+
+    #[derive(Debug)]
+    struct Cyclic;
+
+    impl std::fmt::Display for Cyclic {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "cyclic")
+        }
+    }
+
+    impl std::error::Error for Cyclic {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(self)
+        }
+    }
+
+    #[test]
+    fn test_std_err_source_chain() {
+        let io = Wrapped("config", Some(Box::new(Wrapped("io", None))));
+        let error: Box<dyn std::error::Error + Send + Sync> =
+            Box::new(Wrapped("startup", Some(Box::new(io))));
+        let notice: notice::Error = error.into();
+
+        assert_eq!("std::error::Error", notice.class);
+        assert_eq!(Some("startup".to_owned()), notice.message);
+        let causes = notice.causes.unwrap();
+        assert_eq!(
+            vec![Some("config"), Some("io")],
+            causes
+                .iter()
+                .map(|c| c.message.as_deref())
+                .collect::<Vec<_>>()
+        );
+        assert!(causes.iter().all(|c| c.causes.is_none()));
+
+        let error: Box<dyn std::error::Error> = "std Error".into();
+        let notice: notice::Error = error.into();
+        assert_eq!("std::error::Error", notice.class);
+        assert!(notice.causes.is_none());
+
+        let error: Box<dyn std::error::Error> =
+            Box::new(std::io::Error::new(std::io::ErrorKind::Other, "std Error"));
+        let notice: notice::Error = error.into();
+        assert_eq!("std::io::error::Error", notice.class);
+        assert_eq!(Some("std Error".to_owned()), notice.message);
+    }
+
+    #[test]
+    fn test_std_err_unknown_type() {
+        #[derive(Debug)]
+        enum AppError {
+            Io(u8),
+        }
+
+        impl std::fmt::Display for AppError {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "app error")
+            }
+        }
+
+        impl std::error::Error for AppError {}
+
+        struct Opaque(u64);
+
+        impl std::fmt::Debug for Opaque {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                std::fmt::Display::fmt(self, f)
+            }
+        }
+
+        impl std::fmt::Display for Opaque {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "user {} not found", self.0)
+            }
+        }
+
+        impl std::error::Error for Opaque {}
+
+        let error: Box<dyn std::error::Error> = Box::new(AppError::Io(1));
+        let notice: notice::Error = error.into();
+        assert_eq!("std::error::Error", notice.class);
+        assert_eq!(Some("app error".to_owned()), notice.message);
+
+        let error: Box<dyn std::error::Error> = Box::new(Opaque(42));
+        let notice: notice::Error = error.into();
+        assert_eq!("std::error::Error", notice.class);
+        assert_eq!(Some("user 42 not found".to_owned()), notice.message);
+    }
+
+    #[test]
+    fn test_std_err_source_chain_bounded() {
+        let notice = notice::Error::from(&Cyclic as &(dyn std::error::Error + 'static));
+        assert!(notice.causes.is_none());
+
+        let deep = (0..100).fold(Wrapped("root", None), |inner, _| {
+            Wrapped("wrapper", Some(Box::new(inner)))
+        });
+        let notice = notice::Error::from(&deep as &(dyn std::error::Error + 'static));
+        assert_eq!(notice::MAX_CAUSES, notice.causes.unwrap().len());
+    }
+
+    #[cfg(error_generic_member_access)]
+    #[test]
+    fn test_std_err_backtrace() {
+        #[derive(Debug)]
+        struct Traced(std::backtrace::Backtrace);
+
+        impl std::fmt::Display for Traced {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "traced")
+            }
+        }
+
+        impl std::error::Error for Traced {
+            fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+                request.provide_ref(&self.0);
+            }
+        }
+
+        let error: Box<dyn std::error::Error> =
+            Box::new(Traced(std::backtrace::Backtrace::force_capture()));
+        let notice: notice::Error = error.into();
+
+        assert!(notice
+            .backtrace
+            .unwrap()
+            .iter()
+            .any(|frame| frame.method.contains("test_std_err_backtrace")));
+    }
+
+    #[cfg(feature = "anyhow")]
+    fn classes(causes: Vec<notice::Error>) -> Vec<String> {
+        causes.into_iter().map(|cause| cause.class).collect()
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn test_anyhow_err() {
+        use anyhow::Context;
+
+        let error = "NaN"
+            .parse::<u8>()
+            .context("invalid port")
+            .context("unable to load config")
+            .unwrap_err();
+        let notice: notice::Error = error.into();
+
+        assert_eq!("core::num::error::ParseIntError", notice.class);
+        assert_eq!(Some("unable to load config".to_owned()), notice.message);
+        assert_eq!(
+            vec!["anyhow::Context", "core::num::error::ParseIntError"],
+            classes(notice.causes.unwrap())
+        );
+
+        let notice: notice::Error = anyhow::anyhow!("anyhow Error").into();
+        assert_eq!("anyhow::Error", notice.class);
+        assert!(notice.causes.is_none());
+
+        let error = anyhow::Error::new(Wrapped("db", None))
+            .context("query failed")
+            .context("unable to load user");
+        let notice: notice::Error = error.into();
+        assert_eq!("anyhow::Error", notice.class);
+        assert_eq!(
+            vec!["anyhow::Context", "anyhow::Error"],
+            classes(notice.causes.unwrap())
+        );
+    }
+
+    #[test]
+    fn test_into_notice() {
+        use crate::notice::IntoNotice;
+
+        let error = Wrapped("startup", Some(Box::new(Wrapped("io", None))));
+        let notice = notice::StdError(error).into_notice_error();
+        assert_eq!("honeybadger::notice::tests::Wrapped", notice.class);
+        assert_eq!(Some("startup".to_owned()), notice.message);
+        assert_eq!(1, notice.causes.unwrap().len());
+
+        struct Timeout;
+
+        impl IntoNotice for Timeout {
+            fn class(&self) -> String {
+                "Timeout".to_owned()
+            }
+
+            fn fingerprint(&self) -> Option<String> {
+                Some("timeout".to_owned())
+            }
+        }
+
+        let notice = Timeout.into_notice_error();
+        assert_eq!("Timeout", notice.class);
+        assert_eq!(None, notice.message);
+        assert_eq!(Some("timeout".to_owned()), notice.fingerprint);
+        assert_eq!(notice.class, notice.clone().into_notice_error().class);
+
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+        let notice = error.into_notice_error();
+        assert_eq!("std::io::error::Error", notice.class);
+        assert_eq!(Some("std Error".to_owned()), notice.message);
+    }
+
+    #[test]
+    fn test_std_error_into_notice() {
+        use crate::notice::IntoNotice;
+
+        #[derive(Debug)]
+        struct Declined(Wrapped);
+
+        impl std::fmt::Display for Declined {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "payment declined")
+            }
+        }
+
+        impl std::error::Error for Declined {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        impl IntoNotice for Declined {
+            fn message(&self) -> Option<String> {
+                Some(self.to_string())
+            }
+
+            fn causes(&self) -> Option<Vec<notice::Error>> {
+                Some(notice::Error::source_chain(self))
+            }
+
+            fn tags(&self) -> Vec<String> {
+                vec!["payments".to_owned()]
+            }
+        }
+
+        let notice = Declined(Wrapped("gateway", None)).into_notice_error();
+        assert_eq!(
+            "honeybadger::notice::tests::test_std_error_into_notice::Declined",
+            notice.class
+        );
+        assert_eq!(Some("payment declined".to_owned()), notice.message);
+        assert_eq!(1, notice.causes.unwrap().len());
+        assert_eq!(vec!["payments"], notice.tags);
+    }
+
+    #[test]
+    fn test_frames_from_backtrace() {
+        let backtrace = backtrace::Backtrace::new();
+        let frames = notice::Frame::from_backtrace(&backtrace);
+
+        assert!(frames
+            .iter()
+            .any(|frame| frame.method.contains("test_frames_from_backtrace")));
+    }
+
+    #[test]
+    fn test_frames_from_backtrace_str() {
+        let backtrace = "stack backtrace:
+   0: failure::backtrace::Backtrace::new
+             at /cargo/failure-0.1.8/src/backtrace/mod.rs:111:35
+   1: 0x55d1e2f0 - my_app::run
+             at src/main.rs:12
+   2: std::rt::lang_start";
+        let frames = notice::Frame::from_backtrace_str(backtrace);
+
+        assert_eq!(3, frames.len());
+        assert_eq!("failure::backtrace::Backtrace::new", frames[0].method);
+        assert_eq!("/cargo/failure-0.1.8/src/backtrace/mod.rs", frames[0].file);
+        assert_eq!("111", frames[0].number);
+        assert_eq!(Some("35".to_owned()), frames[0].column);
+        assert_eq!("my_app::run", frames[1].method);
+        assert_eq!("src/main.rs", frames[1].file);
+        assert_eq!("12", frames[1].number);
+        assert_eq!(None, frames[1].column);
+        assert_eq!("", frames[2].file);
+    }
+
+    fn frame(method: &str, file: &str) -> notice::Frame {
+        notice::Frame {
+            number: "1".to_owned(),
+            file: file.to_owned(),
+            method: method.to_owned(),
+            column: None,
+            context: None,
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_frames() {
+        let frames = vec![
+            frame("backtrace::backtrace::trace", "/cargo/backtrace/src/lib.rs"),
+            frame("honeybadger::notice::Frame::from_backtrace", "/build/src/notice.rs"),
+            frame("my_app::run", "/build/my_app/src/main.rs"),
+            frame("my_app::helper", "src/helper.rs"),
+            frame(
+                "serde_json::de::from_str",
+                "/home/ci/.cargo/registry/src/github.com-1ecc6299db9ec823/serde_json-1.0.44/src/de.rs",
+            ),
+            frame("<honeybadger::Honeybadger as Clone>::clone", "/build/src/lib.rs"),
+            frame("std::rt::lang_start", "/rustc/abcdef/src/libstd/rt.rs"),
+        ];
+        let frames = notice::Frame::normalize(frames, "/build/my_app/");
+
+        assert_eq!(4, frames.len());
+        assert_eq!("[PROJECT_ROOT]/src/main.rs", frames[0].file);
+        assert_eq!(Some(notice::FrameContext::App), frames[0].context);
+        assert_eq!("[PROJECT_ROOT]/src/helper.rs", frames[1].file);
+        assert_eq!(Some(notice::FrameContext::App), frames[1].context);
+        assert_eq!("[CRATES]/serde_json-1.0.44/src/de.rs", frames[2].file);
+        assert_eq!(Some(notice::FrameContext::All), frames[2].context);
+        assert_eq!("/rustc/abcdef/src/libstd/rt.rs", frames[3].file);
+        assert_eq!(Some(notice::FrameContext::All), frames[3].context);
+    }
+
+    #[test]
+    fn test_notice_roundtrip() {
+        fn assert_owned<T: Send + Sync + 'static>() {}
+        assert_owned::<notice::Notice>();
+
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "test_error_message");
+        let notice = notice::Notice {
+            api_key: "dummy-api-key".to_owned(),
+            notifier: notice::Notifier {
+                name: "honeybadger".to_owned(),
+                url: "https://example.com".to_owned(),
+                version: "0.0.0".to_owned(),
+            },
+            error: notice::IntoNotice::into_notice_error(error),
+            request: notice::Request {
+                context: Some(
+                    vec![("user_id".to_owned(), serde_json::json!(42))]
+                        .into_iter()
+                        .collect(),
+                ),
+                cgi_data: Default::default(),
+            },
+            server: notice::Server {
+                project_root: "/build".to_owned(),
+                environment_name: "test".to_owned(),
+                hostname: "localhost".to_owned(),
+                time: 0,
+                pid: 1,
+            },
+        };
+
+        let data = serde_json::to_vec(&notice).unwrap();
+        let replayed: notice::Notice = serde_json::from_slice(&data).unwrap();
+
+        assert_eq!("std::io::error::Error", replayed.error.class);
+        assert_eq!(
+            Some(&serde_json::json!(42)),
+            replayed.request.context.unwrap().get("user_id")
+        );
+        assert_eq!(data, serde_json::to_vec(&notice).unwrap());
     }
 }
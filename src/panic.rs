@@ -0,0 +1,226 @@
+//! Panic hook reporting panics to Honeybadger
+//!
+//! The hook converts the panic into a [`notice::Error`](../notice/struct.Error.html) and delivers
+//! it synchronously, before the process unwinds or aborts.
+//!
+//! If the process may not be able to deliver the notice, because it is built with
+//! `panic = "abort"` or panics while shutting down, configure a crash spool with
+//! [`ConfigBuilder::with_crash_dir`](../struct.ConfigBuilder.html#method.with_crash_dir). The
+//! notice is then written to the crash spool before it is delivered, and a notice left in the
+//! crash spool is delivered by the next `Honeybadger` instance on startup.
+//!
+//! # Example
+//!
+//! ```rust, no_run
+//! # use honeybadger::{ConfigBuilder, Honeybadger};
+//! # let api_token = "ffffff";
+//! let config = ConfigBuilder::new(api_token).build();
+//! honeybadger::panic::install(Honeybadger::new(config).unwrap());
+//!
+//! panic!("reported to Honeybadger");
+//! ```
+use std::any::Any;
+use std::cell::Cell;
+use std::future::Future;
+use std::panic::{self, PanicHookInfo};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::thread;
+
+use backtrace::Backtrace;
+
+use crate::errors::*;
+use crate::notice;
+use crate::Honeybadger;
+
+const PANIC_CLASS: &str = "panic";
+const DELIVERY_THREAD: &str = "honeybadger-panic";
+
+thread_local! {
+    /// Whether the current thread is delivering a panic notice.
+    static DELIVERING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Function prefixes belonging to the panic machinery of the standard library, which are trimmed
+/// from the top of the backtrace of a panic.
+const PANIC_PREFIXES: &[&str] = &[
+    "backtrace::",
+    "honeybadger::",
+    "alloc::boxed::",
+    "std::panicking::",
+    "std::sys::backtrace::",
+    "core::panicking::",
+    "rust_begin_unwind",
+    "__rustc::",
+];
+
+/// Install a panic hook which reports every panic to Honeybadger.
+///
+/// The notice is delivered on a dedicated thread, or on the dedicated runtime if one is
+/// configured, which the hook waits on, so the hook can be triggered from within an asynchronous
+/// runtime. The previously installed hook is called before
+/// the notice is delivered, so the default panic message is still printed.
+///
+/// If a crash spool is configured, the notice is written to it synchronously before it is
+/// delivered, and removed once delivered.
+///
+/// A panic raised while delivering a panic notice is not reported, so that a failing delivery
+/// does not panic again and again.
+///
+/// # Arguments
+///
+/// * `honeybadger` - `Honeybadger` instance used to deliver the panic notices
+pub fn install(honeybadger: Honeybadger) {
+    let previous = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        previous(info);
+
+        if is_delivering() {
+            return;
+        }
+        let notice = honeybadger.create_notice(notice_error(info), None);
+        if let Err(e) = deliver(&honeybadger, notice) {
+            error!("Unable to report panic to Honeybadger: {}", e);
+        }
+    }));
+}
+
+/// Convert the panic payload, location, thread name and the current backtrace into a
+/// `notice::Error`.
+///
+/// # Arguments
+///
+/// * `info` - the `PanicHookInfo` passed to a panic hook
+pub fn notice_error(info: &PanicHookInfo) -> notice::Error {
+    let thread = thread::current();
+    let thread_name = thread.name().unwrap_or("<unnamed>");
+    let payload = payload_message(info.payload());
+
+    let message = match info.location() {
+        Some(location) => format!(
+            "thread '{}' panicked at '{}', {}:{}:{}",
+            thread_name,
+            payload,
+            location.file(),
+            location.line(),
+            location.column()
+        ),
+        None => format!("thread '{}' panicked at '{}'", thread_name, payload),
+    };
+
+    let backtrace = notice::Frame::from_backtrace(&Backtrace::new())
+        .into_iter()
+        .skip_while(|frame| {
+            let method = frame.method.trim_start_matches('<');
+            PANIC_PREFIXES
+                .iter()
+                .any(|prefix| method.starts_with(prefix))
+        })
+        .collect();
+
+    notice::Error {
+        class: PANIC_CLASS.to_owned(),
+        message: Some(message),
+        causes: None,
+        backtrace: Some(backtrace),
+        fingerprint: None,
+        tags: Vec::new(),
+    }
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "Box<dyn Any>"
+    }
+}
+
+fn deliver(honeybadger: &Honeybadger, notice: notice::Notice) -> Result<()> {
+    let client = honeybadger.clone();
+    honeybadger.block_on(
+        DELIVERY_THREAD,
+        Delivery(Box::pin(async move { client.send_crash(notice).await })),
+    )
+}
+
+/// Whether the current thread is delivering a panic notice, either on the delivery thread of the
+/// hook or while polling the delivery on the dedicated runtime.
+fn is_delivering() -> bool {
+    DELIVERING.with(Cell::get) || thread::current().name() == Some(DELIVERY_THREAD)
+}
+
+/// Future delivering a panic notice, which flags the thread polling it as delivering.
+struct Delivery<F>(Pin<Box<F>>);
+
+impl<F: Future> Future for Delivery<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let previous = DELIVERING.with(|delivering| delivering.replace(true));
+        let _reset = Reset(previous);
+        self.0.as_mut().poll(cx)
+    }
+}
+
+/// Restores the delivering flag of the thread, including when the delivery panics.
+struct Reset(bool);
+
+impl Drop for Reset {
+    fn drop(&mut self) {
+        DELIVERING.with(|delivering| delivering.set(self.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::panic::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_notice_error() {
+        let captured = Arc::new(Mutex::new(None));
+        let hook_captured = captured.clone();
+
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            *hook_captured.lock().unwrap() = Some(notice_error(info));
+        }));
+        let result = thread::Builder::new()
+            .name("panicking".to_owned())
+            .spawn(|| panic!("test panic {}", 42))
+            .unwrap()
+            .join();
+        panic::set_hook(previous);
+
+        assert!(result.is_err());
+        let error = captured.lock().unwrap().take().unwrap();
+        assert_eq!("panic", error.class);
+        let message = error.message.unwrap();
+        assert!(
+            message.starts_with("thread 'panicking' panicked at 'test panic 42', src/panic.rs:")
+        );
+        assert!(!error.backtrace.unwrap()[0]
+            .method
+            .starts_with("std::panicking::"));
+    }
+
+    #[test]
+    fn test_is_delivering() {
+        assert!(!is_delivering());
+        let delivering = crate::executor::block_on(Delivery(Box::pin(async { is_delivering() })));
+        assert!(delivering.unwrap());
+        assert!(!is_delivering());
+
+        let delivering = thread::Builder::new()
+            .name(DELIVERY_THREAD.to_owned())
+            .spawn(is_delivering)
+            .unwrap()
+            .join();
+        assert!(delivering.unwrap());
+    }
+}
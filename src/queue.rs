@@ -0,0 +1,403 @@
+//! Bounded queue of notices, drained by a background delivery worker
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+use crate::errors::*;
+use crate::executor;
+use crate::notice::Notice;
+
+/// Behaviour of the background queue when a notice is queued while the queue is full.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OverflowPolicy {
+    /// Discard the notice being queued.
+    DropNewest,
+    /// Discard the oldest queued notice to make room for the notice being queued.
+    DropOldest,
+    /// Wait until the worker has made room in the queue.
+    Block,
+}
+
+/// Counters of the notices handled by the background queue.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct QueueStats {
+    /// Notices discarded because the queue was full.
+    pub dropped: u64,
+    /// Notices accepted by the Honeybadger API.
+    pub delivered: u64,
+    /// Notices that could not be delivered to the Honeybadger API.
+    pub failed: u64,
+}
+
+/// Outcome of flushing the background queue.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FlushStats {
+    /// Notices accepted by the Honeybadger API while flushing.
+    pub delivered: u64,
+    /// Notices that could not be delivered to the Honeybadger API while flushing.
+    pub failed: u64,
+    /// Notices still queued or in flight when the deadline expired.
+    pub abandoned: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct Queue {
+    notices: Mutex<VecDeque<Notice>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    available: Notify,
+    space: Notify,
+    idle: Notify,
+    pending: AtomicUsize,
+    started: AtomicBool,
+    dropped: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl Queue {
+    pub(crate) fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        Queue {
+            notices: Mutex::new(VecDeque::new()),
+            capacity: capacity.max(1),
+            policy,
+            available: Notify::new(),
+            space: Notify::new(),
+            idle: Notify::new(),
+            pending: AtomicUsize::new(0),
+            started: AtomicBool::new(false),
+            dropped: AtomicU64::new(0),
+            delivered: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns `true` for the caller that should spawn the worker, once until the worker is
+    /// dropped.
+    pub(crate) fn start(&self) -> bool {
+        !self.started.swap(true, Ordering::SeqCst)
+    }
+
+    /// Number of notices queued or in flight.
+    pub(crate) fn pending(&self) -> u64 {
+        self.pending.load(Ordering::SeqCst) as u64
+    }
+
+    pub(crate) fn stats(&self) -> QueueStats {
+        QueueStats {
+            dropped: self.dropped.load(Ordering::SeqCst),
+            delivered: self.delivered.load(Ordering::SeqCst),
+            failed: self.failed.load(Ordering::SeqCst),
+        }
+    }
+
+    pub(crate) async fn push(&self, notice: Notice) {
+        loop {
+            {
+                let mut notices = self.lock();
+                if notices.len() < self.capacity {
+                    notices.push_back(notice);
+                    self.pending.fetch_add(1, Ordering::SeqCst);
+                    self.available.notify();
+                    return;
+                }
+                match self.policy {
+                    OverflowPolicy::DropNewest => {
+                        self.dropped.fetch_add(1, Ordering::SeqCst);
+                        warn!("Honeybadger queue is full, dropping the newest notice");
+                        return;
+                    }
+                    OverflowPolicy::DropOldest => {
+                        notices.pop_front();
+                        notices.push_back(notice);
+                        self.dropped.fetch_add(1, Ordering::SeqCst);
+                        warn!("Honeybadger queue is full, dropping the oldest notice");
+                        return;
+                    }
+                    OverflowPolicy::Block => {}
+                }
+            }
+            self.space.notified().await;
+        }
+    }
+
+    async fn pop(&self) -> InFlight<'_> {
+        loop {
+            if let Some(notice) = self.try_pop() {
+                return notice;
+            }
+            self.available.notified().await;
+        }
+    }
+
+    fn try_pop(&self) -> Option<InFlight<'_>> {
+        let notice = self.lock().pop_front()?;
+        self.space.notify();
+        Some(InFlight {
+            queue: self,
+            notice: Some(notice),
+        })
+    }
+
+    /// Put a notice whose delivery was cancelled back at the front of the queue. The notice is
+    /// still counted as pending.
+    fn requeue(&self, notice: Notice) {
+        self.lock().push_front(notice);
+        self.available.notify();
+    }
+
+    /// Record the outcome of a notice that was taken from the queue.
+    fn complete(&self, result: Result<()>) {
+        match result {
+            Ok(_) => {
+                self.delivered.fetch_add(1, Ordering::SeqCst);
+            }
+            Err(e) => {
+                self.failed.fetch_add(1, Ordering::SeqCst);
+                error!("Unable to deliver queued notice to Honeybadger: {}", e);
+            }
+        }
+        if self.pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.idle.notify();
+        }
+    }
+
+    async fn wait_idle(&self) {
+        while self.pending.load(Ordering::SeqCst) > 0 {
+            self.idle.notified().await;
+        }
+        // pass the wakeup on to any other task waiting on an idle queue
+        self.idle.notify();
+    }
+
+    /// Deliver the queued notices using the `send` function, alongside the worker, and wait for
+    /// the notices in flight, until the `deadline` expires.
+    pub(crate) async fn flush<F, R>(&self, deadline: Duration, send: F) -> FlushStats
+    where
+        F: Fn(Notice) -> R,
+        R: Future<Output = Result<()>>,
+    {
+        let before = self.stats();
+        let drain = async {
+            while let Some(in_flight) = self.try_pop() {
+                let result = send(in_flight.notice()).await;
+                in_flight.complete(result);
+            }
+            self.wait_idle().await;
+        };
+        if executor::timeout(deadline, drain).await.is_none() {
+            warn!(
+                "Honeybadger queue could not be flushed within {:?}",
+                deadline
+            );
+        }
+
+        let after = self.stats();
+        FlushStats {
+            delivered: after.delivered - before.delivered,
+            failed: after.failed - before.failed,
+            abandoned: self.pending(),
+        }
+    }
+
+    /// Deliver queued notices one at a time, using the `send` function.
+    pub(crate) async fn drain<F, R>(self: Arc<Self>, send: F)
+    where
+        F: Fn(Notice) -> R,
+        R: Future<Output = Result<()>>,
+    {
+        let _worker = Worker(&self);
+        loop {
+            let in_flight = self.pop().await;
+            let result = send(in_flight.notice()).await;
+            in_flight.complete(result);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<Notice>> {
+        self.notices.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Running worker, which lets the next notice queued start a new worker once it is dropped,
+/// e.g. with the runtime it was spawned on.
+struct Worker<'a>(&'a Queue);
+
+impl Drop for Worker<'_> {
+    fn drop(&mut self) {
+        self.0.started.store(false, Ordering::SeqCst);
+    }
+}
+
+/// A notice taken from the queue, which is put back at the front of the queue if its delivery is
+/// cancelled before its outcome is recorded, e.g. by the deadline of a flush.
+struct InFlight<'a> {
+    queue: &'a Queue,
+    notice: Option<Notice>,
+}
+
+impl InFlight<'_> {
+    fn notice(&self) -> Notice {
+        self.notice.clone().expect("notice in flight")
+    }
+
+    fn complete(mut self, result: Result<()>) {
+        self.notice = None;
+        self.queue.complete(result);
+    }
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if let Some(notice) = self.notice.take() {
+            self.queue.requeue(notice);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tokio-runtime"))]
+mod tests {
+
+    use crate::notice::test_notice as notice;
+    use crate::queue::*;
+    use tokio::runtime::Runtime;
+
+    fn classes(queue: &Queue) -> Vec<String> {
+        queue.lock().iter().map(|n| n.error.class.clone()).collect()
+    }
+
+    #[test]
+    fn test_drop_newest() {
+        let queue = Queue::new(2, OverflowPolicy::DropNewest);
+        let mut rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            for class in &["a", "b", "c"] {
+                queue.push(notice(class)).await;
+            }
+        });
+
+        assert_eq!(vec!["a", "b"], classes(&queue));
+        assert_eq!(1, queue.stats().dropped);
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let queue = Queue::new(2, OverflowPolicy::DropOldest);
+        let mut rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            for class in &["a", "b", "c"] {
+                queue.push(notice(class)).await;
+            }
+        });
+
+        assert_eq!(vec!["b", "c"], classes(&queue));
+        assert_eq!(1, queue.stats().dropped);
+    }
+
+    #[test]
+    fn test_block_and_drain() {
+        let queue = Arc::new(Queue::new(1, OverflowPolicy::Block));
+        let mut rt = Runtime::new().unwrap();
+        let delivered = Arc::new(Mutex::new(Vec::new()));
+
+        let worker_delivered = delivered.clone();
+        rt.spawn(queue.clone().drain(move |notice| {
+            let delivered = worker_delivered.clone();
+            async move {
+                delivered.lock().unwrap().push(notice.error.class);
+                Ok(())
+            }
+        }));
+        rt.block_on(async {
+            for class in &["a", "b", "c"] {
+                queue.push(notice(class)).await;
+            }
+            while queue.stats().delivered < 3 {
+                tokio::time::delay_for(std::time::Duration::from_millis(1)).await;
+            }
+        });
+
+        assert_eq!(vec!["a", "b", "c"], *delivered.lock().unwrap());
+        assert_eq!(
+            QueueStats {
+                dropped: 0,
+                delivered: 3,
+                failed: 0
+            },
+            queue.stats()
+        );
+    }
+
+    #[test]
+    fn test_flush() {
+        let queue = Queue::new(10, OverflowPolicy::DropNewest);
+        let mut rt = Runtime::new().unwrap();
+        let stats = rt.block_on(async {
+            for class in &["a", "b", "fail"] {
+                queue.push(notice(class)).await;
+            }
+            queue
+                .flush(Duration::from_secs(1), |notice| async move {
+                    if notice.error.class == "fail" {
+                        Err(Error::Transport("failed".to_owned()))
+                    } else {
+                        Ok(())
+                    }
+                })
+                .await
+        });
+
+        assert_eq!(
+            FlushStats {
+                delivered: 2,
+                failed: 1,
+                abandoned: 0
+            },
+            stats
+        );
+    }
+
+    #[test]
+    fn test_flush_deadline() {
+        let queue = Queue::new(10, OverflowPolicy::DropNewest);
+        let mut rt = Runtime::new().unwrap();
+        let stats = rt.block_on(async {
+            for class in &["a", "b"] {
+                queue.push(notice(class)).await;
+            }
+            queue
+                .flush(Duration::from_millis(10), |_| async move {
+                    tokio::time::delay_for(Duration::from_secs(1)).await;
+                    Ok(())
+                })
+                .await
+        });
+
+        assert_eq!(
+            FlushStats {
+                delivered: 0,
+                failed: 0,
+                abandoned: 2
+            },
+            stats
+        );
+        // the notice in flight when the deadline expired is put back in the queue
+        assert_eq!(vec!["a", "b"], classes(&queue));
+
+        let stats = rt.block_on(queue.flush(Duration::from_secs(1), |_| async { Ok(()) }));
+
+        assert_eq!(
+            FlushStats {
+                delivered: 2,
+                failed: 0,
+                abandoned: 0
+            },
+            stats
+        );
+        assert_eq!(0, queue.pending());
+    }
+}
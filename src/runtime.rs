@@ -0,0 +1,161 @@
+//! Dedicated runtime delivering notices, independently of the runtime of the host application
+use std::future::Future;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+use tokio::runtime::{Builder, Handle, Runtime};
+
+use crate::errors::*;
+
+const RUNTIME_THREAD_NAME: &str = "honeybadger-delivery";
+const RUNTIME_SHUTDOWN_THREAD_NAME: &str = "honeybadger-shutdown";
+const RUNTIME_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Multi-threaded Tokio runtime owned by the client.
+///
+/// Futures are spawned on the runtime, and their outcome is sent back to the caller, so a caller
+/// may wait on a future from any thread, or from another runtime, even while that runtime is
+/// shutting down.
+///
+/// Clones share the runtime, which is shut down when the last clone owning it is dropped. Tasks
+/// spawned on the runtime hold a [`detached`](#method.detached) clone, which does not keep the
+/// runtime alive.
+#[derive(Clone, Debug)]
+pub(crate) struct DeliveryRuntime {
+    handle: Handle,
+    /// Held for its `Drop` only, `None` for a detached clone.
+    _owner: Option<Arc<Owner>>,
+}
+
+/// Owner of the runtime, shutting it down on a plain thread when dropped, since a runtime cannot
+/// be dropped from an asynchronous context, including its own worker threads.
+#[derive(Debug)]
+struct Owner(Option<Runtime>);
+
+impl DeliveryRuntime {
+    pub(crate) fn new(threads: usize) -> Result<Self> {
+        let runtime = Builder::new()
+            .threaded_scheduler()
+            .core_threads(threads.max(1))
+            .thread_name(RUNTIME_THREAD_NAME)
+            .enable_all()
+            .build()?;
+        Ok(DeliveryRuntime {
+            handle: runtime.handle().clone(),
+            _owner: Some(Arc::new(Owner(Some(runtime)))),
+        })
+    }
+
+    /// A clone which does not keep the runtime alive, for the tasks spawned on the runtime.
+    /// Futures spawned once the runtime is shut down are dropped.
+    pub(crate) fn detached(&self) -> Self {
+        DeliveryRuntime {
+            handle: self.handle.clone(),
+            _owner: None,
+        }
+    }
+
+    /// Whether the current thread is a worker thread of a delivery runtime, which must not block
+    /// on the runtime.
+    pub(crate) fn is_worker_thread() -> bool {
+        thread::current().name() == Some(RUNTIME_THREAD_NAME)
+    }
+
+    pub(crate) fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.handle.spawn(future);
+    }
+
+    /// Run a future on the runtime, and wait for its outcome from the runtime of the caller.
+    pub(crate) async fn run<F, T>(&self, future: F) -> Result<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        self.handle
+            .spawn(future)
+            .await
+            .map_err(|e| Error::Runtime(format!("Honeybadger delivery task failed: {}", e)))
+    }
+
+    /// Run a future on the runtime, blocking the current thread until it completes. Must not be
+    /// called from a worker thread of the runtime, see
+    /// [`is_worker_thread`](#method.is_worker_thread).
+    pub(crate) fn block_on<F, T>(&self, future: F) -> Result<T>
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (tx, rx) = mpsc::sync_channel(1);
+        self.handle.spawn(async move {
+            let _ = tx.send(future.await);
+        });
+        rx.recv().map_err(|_| {
+            Error::Runtime(
+                "Honeybadger delivery runtime stopped before the future completed".to_owned(),
+            )
+        })
+    }
+}
+
+impl Drop for Owner {
+    fn drop(&mut self) {
+        let runtime = match self.0.take() {
+            Some(runtime) => runtime,
+            None => return,
+        };
+        let (tx, rx) = mpsc::channel();
+        let spawned = thread::Builder::new()
+            .name(RUNTIME_SHUTDOWN_THREAD_NAME.to_owned())
+            .spawn(move || {
+                let runtime = rx.recv().expect("runtime sent to the shutdown thread");
+                Runtime::shutdown_timeout(runtime, RUNTIME_SHUTDOWN_TIMEOUT);
+            });
+        match spawned {
+            Ok(_) => {
+                let _ = tx.send(runtime);
+            }
+            Err(e) => {
+                error!(
+                    "Unable to shut down the Honeybadger delivery runtime: {}",
+                    e
+                );
+                std::mem::forget(runtime);
+            }
+        }
+    }
+}
+
+/// Wait until the runtime is shut down, and the futures spawned on it are dropped.
+#[cfg(test)]
+pub(crate) fn assert_shut_down(runtime: &DeliveryRuntime) {
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while runtime.block_on(async {}).is_ok() {
+        assert!(
+            std::time::Instant::now() < deadline,
+            "the runtime was not shut down"
+        );
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::runtime::*;
+
+    #[test]
+    fn test_drop_within_runtime() {
+        let runtime = DeliveryRuntime::new(1).unwrap();
+        let detached = runtime.detached();
+        assert_eq!(42, runtime.block_on(async { 42 }).unwrap());
+
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async move { drop(runtime) });
+
+        assert_shut_down(&detached);
+    }
+}
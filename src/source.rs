@@ -0,0 +1,113 @@
+//! Source code snippets attached to the in-project frames of a backtrace
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::notice::{self, FrameContext, PROJECT_ROOT};
+
+/// Cache of project source files, shared across notices.
+///
+/// Files that cannot be read, e.g. because the sources are not shipped with a deployed
+/// container, are remembered as missing and skipped on subsequent notices.
+#[derive(Debug, Default)]
+pub(crate) struct SourceCache {
+    files: Mutex<HashMap<PathBuf, Option<Arc<Vec<String>>>>>,
+}
+
+impl SourceCache {
+    /// Attach the lines surrounding each in-project frame of the error and its causes, `radius`
+    /// lines before and after the line of the frame.
+    pub(crate) fn attach(&self, error: &mut notice::Error, project_root: &str, radius: usize) {
+        for frame in error.backtrace.iter_mut().flatten() {
+            if frame.context != Some(FrameContext::App) {
+                continue;
+            }
+            let relative = match frame.file.strip_prefix(PROJECT_ROOT) {
+                Some(relative) => relative.trim_start_matches('/'),
+                None => continue,
+            };
+            let number: usize = match frame.number.parse() {
+                Ok(number) if number > 0 => number,
+                _ => continue,
+            };
+            if let Some(lines) = self.lines(&Path::new(project_root).join(relative)) {
+                let source: BTreeMap<usize, String> = lines
+                    .iter()
+                    .enumerate()
+                    .map(|(index, line)| (index + 1, line))
+                    .skip(number.saturating_sub(radius + 1))
+                    .take_while(|(line_number, _)| *line_number <= number + radius)
+                    .map(|(line_number, line)| (line_number, line.to_owned()))
+                    .collect();
+                if !source.is_empty() {
+                    frame.source = Some(source);
+                }
+            }
+        }
+
+        for cause in error.causes.iter_mut().flatten() {
+            self.attach(cause, project_root, radius);
+        }
+    }
+
+    fn lines(&self, path: &Path) -> Option<Arc<Vec<String>>> {
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+        files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| match fs::read_to_string(path) {
+                Ok(content) => Some(Arc::new(content.lines().map(str::to_owned).collect())),
+                Err(e) => {
+                    debug!("Skipping source for {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::notice;
+    use crate::source::SourceCache;
+
+    fn error_with_frame(file: &str, number: &str) -> notice::Error {
+        notice::Error {
+            class: "test".to_owned(),
+            message: None,
+            causes: None,
+            backtrace: Some(vec![notice::Frame {
+                number: number.to_owned(),
+                file: file.to_owned(),
+                method: "honeybadger::source::tests".to_owned(),
+                column: None,
+                context: Some(notice::FrameContext::App),
+                source: None,
+            }]),
+            fingerprint: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_attach_source() {
+        let cache = SourceCache::default();
+        let mut error = error_with_frame("[PROJECT_ROOT]/src/source.rs", "1");
+        cache.attach(&mut error, env!("CARGO_MANIFEST_DIR"), 2);
+
+        let source = error.backtrace.unwrap().remove(0).source.unwrap();
+        assert_eq!(vec![1, 2, 3], source.keys().cloned().collect::<Vec<_>>());
+        assert!(source[&1].starts_with("//! Source code snippets"));
+    }
+
+    #[test]
+    fn test_attach_source_missing_file() {
+        let cache = SourceCache::default();
+        let mut error = error_with_frame("[PROJECT_ROOT]/src/missing.rs", "10");
+        cache.attach(&mut error, env!("CARGO_MANIFEST_DIR"), 2);
+
+        assert!(error.backtrace.unwrap()[0].source.is_none());
+        assert_eq!(1, cache.files.lock().unwrap().len());
+    }
+}
@@ -0,0 +1,302 @@
+//! Disk-backed spool of notices which could not be delivered to the Honeybadger API
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::ops::Deref;
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::errors::*;
+use crate::notice::Notice;
+
+const SPOOL_EXTENSION: &str = "json";
+
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Spool directories whose notices were delivered on start by an instance of this process.
+static STARTED: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+/// Directory of serialized notices, replayed in the order they were written.
+///
+/// Each notice is written to a hidden temporary file and renamed, so a spooled notice is never
+/// partially written. The spool is capped by the total size of its notices, and by their age.
+///
+/// Notices hold the API key and the environment of the process, so on Unix the spool directory
+/// is only accessible by its owner, and notices are only readable by their owner.
+#[derive(Debug)]
+pub(crate) struct Spool {
+    dir: PathBuf,
+    max_bytes: u64,
+    max_age: Duration,
+    replaying: AtomicBool,
+}
+
+impl Spool {
+    pub(crate) fn new(dir: &Path, max_bytes: u64, max_age: Duration) -> Self {
+        Spool {
+            dir: dir.to_path_buf(),
+            max_bytes,
+            max_age,
+            replaying: AtomicBool::new(false),
+        }
+    }
+
+    /// Persist a notice in the spool, and evict the oldest notices exceeding the caps. Returns the
+    /// path of the spooled notice.
+    pub(crate) fn write(&self, notice: &Notice) -> Result<PathBuf> {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        builder.mode(0o700);
+        builder.create(&self.dir)?;
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|v| v.as_nanos())
+            .unwrap_or(0);
+        let name = format!(
+            "{:024}-{:010}-{:010}.{}",
+            nanos,
+            process::id(),
+            SEQUENCE.fetch_add(1, Ordering::SeqCst),
+            SPOOL_EXTENSION
+        );
+        let tmp = self.dir.join(format!(".{}.tmp", name));
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(&tmp)?;
+        file.write_all(&serde_json::to_vec(notice)?)?;
+        file.sync_all()?;
+        let path = self.dir.join(&name);
+        fs::rename(&tmp, &path)?;
+        debug!("Spooled Honeybadger notice to {}", name);
+
+        self.evict();
+        Ok(path)
+    }
+
+    /// Spooled notices, oldest first.
+    pub(crate) fn list(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.extension().is_some_and(|ext| ext == SPOOL_EXTENSION)
+                        && path
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .is_some_and(|name| !name.starts_with('.'))
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        paths.sort();
+        paths
+    }
+
+    /// Read a spooled notice. Notices that cannot be deserialized are removed from the spool.
+    pub(crate) fn read(&self, path: &Path) -> Option<Notice> {
+        let notice = fs::read(path)
+            .map_err(Error::from)
+            .and_then(|data| serde_json::from_slice(&data).map_err(Error::from));
+        match notice {
+            Ok(notice) => Some(notice),
+            Err(e) => {
+                warn!("Discarding spooled notice {}: {}", path.display(), e);
+                self.remove(path);
+                None
+            }
+        }
+    }
+
+    pub(crate) fn remove(&self, path: &Path) {
+        if let Err(e) = fs::remove_file(path) {
+            warn!("Unable to remove spooled notice {}: {}", path.display(), e);
+        }
+    }
+
+    /// Returns `true` for the first caller of this process, which delivers the notices left in
+    /// the spool by a previous process.
+    pub(crate) fn claim_startup(&self) -> bool {
+        let mut started = STARTED.lock().unwrap_or_else(|e| e.into_inner());
+        started
+            .get_or_insert_with(HashSet::new)
+            .insert(self.dir.clone())
+    }
+
+    /// Returns a replay if the caller should replay the spool, and no other replay is running.
+    pub(crate) fn start_replay(self: &Arc<Self>) -> Option<Replay> {
+        if self.list().is_empty() || self.replaying.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        Some(Replay(self.clone()))
+    }
+
+    /// Remove the notices older than the maximum age, and the oldest notices exceeding the
+    /// maximum size of the spool.
+    pub(crate) fn evict(&self) {
+        let mut entries: Vec<(PathBuf, u64)> = Vec::new();
+        for path in self.list() {
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            let expired = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > self.max_age);
+            if expired {
+                debug!("Evicting expired spooled notice {}", path.display());
+                self.remove(&path);
+            } else {
+                entries.push((path, metadata.len()));
+            }
+        }
+
+        let mut total: u64 = entries.iter().map(|(_, len)| len).sum();
+        for (path, len) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            warn!("Honeybadger spool is full, evicting {}", path.display());
+            self.remove(&path);
+            total -= len;
+        }
+    }
+}
+
+/// Replay of a spool, which lets the next replay start once dropped, even if the task replaying
+/// the spool was cancelled.
+#[derive(Debug)]
+pub(crate) struct Replay(Arc<Spool>);
+
+impl Deref for Replay {
+    type Target = Spool;
+
+    fn deref(&self) -> &Spool {
+        &self.0
+    }
+}
+
+impl Drop for Replay {
+    fn drop(&mut self) {
+        self.0.replaying.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::notice::test_notice as notice;
+    use crate::spool::*;
+    use std::env;
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt;
+
+    fn spool_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("honeybadger-spool-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_write_and_read_in_order() {
+        let dir = spool_dir("order");
+        let spool = Spool::new(&dir, 1024 * 1024, Duration::from_secs(60));
+
+        for class in &["a", "b", "c"] {
+            spool.write(&notice(class)).unwrap();
+        }
+        let classes: Vec<String> = spool
+            .list()
+            .iter()
+            .filter_map(|path| spool.read(path))
+            .map(|notice| notice.error.class)
+            .collect();
+
+        assert_eq!(vec!["a", "b", "c"], classes);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_evict_oldest_over_size() {
+        let dir = spool_dir("size");
+        let size = serde_json::to_vec(&notice("a")).unwrap().len() as u64;
+        let spool = Spool::new(&dir, size * 2, Duration::from_secs(60));
+
+        for class in &["a", "b", "c"] {
+            spool.write(&notice(class)).unwrap();
+        }
+        let paths = spool.list();
+
+        assert_eq!(2, paths.len());
+        assert_eq!("b", spool.read(&paths[0]).unwrap().error.class);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_owner_only() {
+        let dir = spool_dir("mode");
+        let spool = Spool::new(&dir, 1024 * 1024, Duration::from_secs(60));
+
+        let path = spool.write(&notice("a")).unwrap();
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(0o700, mode(&dir));
+        assert_eq!(0o600, mode(&path));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_replay_released_on_drop() {
+        let dir = spool_dir("replay");
+        let spool = Arc::new(Spool::new(&dir, 1024 * 1024, Duration::from_secs(60)));
+
+        assert!(spool.start_replay().is_none());
+        spool.write(&notice("a")).unwrap();
+        let replay = spool.start_replay().unwrap();
+        assert!(spool.start_replay().is_none());
+
+        drop(replay);
+        assert!(spool.start_replay().is_some());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_claim_startup_once() {
+        let dir = spool_dir("startup");
+        let spool = Spool::new(&dir, 1024 * 1024, Duration::from_secs(60));
+
+        assert!(spool.claim_startup());
+        assert!(!spool.claim_startup());
+        assert!(!Spool::new(&dir, 1024, Duration::from_secs(1)).claim_startup());
+    }
+
+    #[test]
+    fn test_evict_expired_and_corrupted() {
+        let dir = spool_dir("expired");
+        let spool = Spool::new(&dir, 1024 * 1024, Duration::from_secs(0));
+
+        spool.write(&notice("a")).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        spool.evict();
+        assert!(spool.list().is_empty());
+
+        fs::write(dir.join("0-corrupted.json"), b"{").unwrap();
+        let paths = spool.list();
+        assert_eq!(1, paths.len());
+        assert!(spool.read(&paths[0]).is_none());
+        assert!(spool.list().is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
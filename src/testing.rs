@@ -0,0 +1,283 @@
+//! In-memory transport and assertions, to test error reporting without the Honeybadger API
+//!
+//! # Example
+//!
+//! ```rust
+//! # use honeybadger::ConfigBuilder;
+//! # use std::collections::HashMap;
+//! use honeybadger::testing;
+//! use tokio::runtime::Runtime;
+//!
+//! let config = ConfigBuilder::new("test-api-key").build();
+//! let (honeybadger, transport) = testing::capture(config);
+//!
+//! let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+//! let context: HashMap<&str, &str> = [("user_id", "42")].iter().cloned().collect();
+//!
+//! let mut rt = Runtime::new().unwrap();
+//! rt.block_on(honeybadger.notify(error, Some(context))).unwrap();
+//!
+//! transport.assert_notified("std::io::error::Error");
+//! transport.assert_context("user_id", "42");
+//! ```
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+use crate::honeybadger::Config;
+use crate::notice::Notice;
+use crate::transport::{Transport, TransportFuture};
+use crate::Honeybadger;
+
+/// Transport recording every notice in memory, instead of sending it.
+///
+/// Clones share the same recorded notices, so a clone may be handed to
+/// [`Honeybadger::with_transport`](../struct.Honeybadger.html#method.with_transport) while the
+/// original is kept for assertions. The assertion methods panic with the recorded notices when
+/// they fail, like `assert!`.
+#[derive(Clone, Debug)]
+pub struct CapturingTransport {
+    notices: Arc<Mutex<Vec<Notice>>>,
+    status: Arc<AtomicU16>,
+}
+
+/// Constructs a `Honeybadger` instance delivering its notices to a new `CapturingTransport`.
+/// Panic notices left in the crash spool by a previous process are not delivered.
+///
+/// # Arguments
+///
+/// * `config` - `Config` instance, which is built using the `ConfigBuilder`
+pub fn capture(config: Config) -> (Honeybadger, CapturingTransport) {
+    let transport = CapturingTransport::new();
+    let honeybadger = Honeybadger::construct(config, transport.clone())
+        .expect("Unable to construct a Honeybadger instance");
+    (honeybadger, transport)
+}
+
+impl CapturingTransport {
+    /// Constructs a transport which accepts every notice with a `201 Created` response.
+    pub fn new() -> Self {
+        CapturingTransport {
+            notices: Arc::new(Mutex::new(Vec::new())),
+            status: Arc::new(AtomicU16::new(201)),
+        }
+    }
+
+    /// Override the status of the responses, to simulate a failing Honeybadger API. Notices are
+    /// recorded regardless of the status.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The HTTP status code of the next responses
+    pub fn respond_with(&self, status: u16) {
+        self.status.store(status, Ordering::SeqCst);
+    }
+
+    /// The recorded notices, oldest first.
+    pub fn notices(&self) -> Vec<Notice> {
+        self.notices.lock().unwrap().clone()
+    }
+
+    /// The most recently recorded notice.
+    pub fn last_notice(&self) -> Option<Notice> {
+        self.notices.lock().unwrap().last().cloned()
+    }
+
+    /// Forget the recorded notices.
+    pub fn clear(&self) {
+        self.notices.lock().unwrap().clear();
+    }
+
+    /// Assert that a notice with the given error class was recorded, and return the most recent
+    /// one.
+    pub fn assert_notified(&self, class: &str) -> Notice {
+        let notices = self.notices.lock().unwrap();
+        match notices
+            .iter()
+            .rev()
+            .find(|notice| notice.error.class == class)
+        {
+            Some(notice) => notice.clone(),
+            None => panic!(
+                "expected a notice with class `{}`, recorded classes: {:?}",
+                class,
+                Self::classes(&notices)
+            ),
+        }
+    }
+
+    /// Assert that no notice with the given error class was recorded.
+    pub fn assert_not_notified(&self, class: &str) {
+        let notices = self.notices.lock().unwrap();
+        if notices.iter().any(|notice| notice.error.class == class) {
+            panic!(
+                "expected no notice with class `{}`, recorded classes: {:?}",
+                class,
+                Self::classes(&notices)
+            );
+        }
+    }
+
+    /// Assert that the most recent notice carries the given context value.
+    pub fn assert_context<V: Into<Value>>(&self, key: &str, value: V) {
+        let notice = self.expect_last_notice();
+        let value = value.into();
+        let actual = notice
+            .request
+            .context
+            .as_ref()
+            .and_then(|context| context.get(key));
+        if actual != Some(&value) {
+            panic!(
+                "expected context `{}` to be {}, was {:?}",
+                key, value, actual
+            );
+        }
+    }
+
+    /// Assert that the most recent notice carries the given tag.
+    pub fn assert_tagged(&self, tag: &str) {
+        let notice = self.expect_last_notice();
+        if !notice.error.tags.iter().any(|t| t == tag) {
+            panic!(
+                "expected tag `{}`, recorded tags: {:?}",
+                tag, notice.error.tags
+            );
+        }
+    }
+
+    fn expect_last_notice(&self) -> Notice {
+        self.last_notice()
+            .unwrap_or_else(|| panic!("expected a notice, but none was recorded"))
+    }
+
+    fn classes(notices: &[Notice]) -> Vec<&str> {
+        notices
+            .iter()
+            .map(|notice| notice.error.class.as_str())
+            .collect()
+    }
+}
+
+impl Default for CapturingTransport {
+    fn default() -> Self {
+        CapturingTransport::new()
+    }
+}
+
+impl Transport for CapturingTransport {
+    fn send(&self, request: http::Request<Vec<u8>>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let notice: Notice = serde_json::from_slice(request.body())?;
+            self.notices.lock().unwrap().push(notice);
+            Ok(http::Response::builder()
+                .status(self.status.load(Ordering::SeqCst))
+                .body(Vec::new())?)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::errors::*;
+    use crate::executor;
+    use crate::testing::*;
+    use crate::{ConfigBuilder, FlushGuard};
+    use std::time::Duration;
+
+    #[test]
+    fn test_capture() {
+        let config = ConfigBuilder::new("dummy-api-key").build();
+        let (honeybadger, transport) = capture(config);
+
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+        let mut notice = honeybadger.create_notice(error, None);
+        notice.error.tags.push("db".to_owned());
+        executor::block_on(honeybadger.send(notice))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(1, transport.notices().len());
+        transport.assert_notified("std::io::error::Error");
+        transport.assert_not_notified("panic");
+        transport.assert_tagged("db");
+        assert!(transport.last_notice().unwrap().request.context.is_none());
+
+        transport.clear();
+        assert!(transport.last_notice().is_none());
+    }
+
+    struct Declined(u64);
+
+    impl crate::IntoNotice for Declined {
+        fn tags(&self) -> Vec<String> {
+            vec!["payments".to_owned()]
+        }
+
+        fn context(&self) -> std::collections::HashMap<String, serde_json::Value> {
+            vec![
+                ("order_id".to_owned(), self.0.into()),
+                ("user_id".to_owned(), "unknown".into()),
+            ]
+            .into_iter()
+            .collect()
+        }
+
+        fn is_ignored(&self) -> bool {
+            self.0 == 0
+        }
+    }
+
+    #[test]
+    fn test_capture_into_notice() {
+        let config = ConfigBuilder::new("dummy-api-key").build();
+        let (honeybadger, transport) = capture(config);
+
+        let context = vec![("user_id", "7")].into_iter().collect();
+        executor::block_on(honeybadger.notify(Declined(42), Some(context)))
+            .unwrap()
+            .unwrap();
+
+        transport.assert_notified("honeybadger::testing::tests::Declined");
+        transport.assert_tagged("payments");
+        transport.assert_context("order_id", 42);
+        transport.assert_context("user_id", "7");
+
+        executor::block_on(honeybadger.notify(Declined(0), None))
+            .unwrap()
+            .unwrap();
+        assert_eq!(1, transport.notices().len());
+    }
+
+    #[test]
+    fn test_flush_guard() {
+        let config = ConfigBuilder::new("dummy-api-key").build();
+        let (honeybadger, transport) = capture(config);
+        let guard = FlushGuard::new(honeybadger.clone(), Duration::from_secs(5));
+
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+        executor::block_on(honeybadger.enqueue(error, None)).unwrap();
+        drop(guard);
+
+        assert_eq!(0, honeybadger.pending());
+        assert_eq!(1, honeybadger.queue_stats().delivered);
+        transport.assert_notified("std::io::error::Error");
+    }
+
+    #[test]
+    fn test_respond_with() {
+        let config = ConfigBuilder::new("dummy-api-key")
+            .with_max_retries(0)
+            .build();
+        let (honeybadger, transport) = capture(config);
+        transport.respond_with(401);
+
+        let error = std::io::Error::new(std::io::ErrorKind::Other, "std Error");
+        let res = executor::block_on(honeybadger.notify(error, None)).unwrap();
+
+        assert!(matches!(res, Err(Error::Unauthorized { .. })));
+        transport.assert_notified("std::io::error::Error");
+    }
+}
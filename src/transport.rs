@@ -0,0 +1,192 @@
+//! Transports delivering serialized notices to the Honeybadger API
+use std::future::Future;
+use std::pin::Pin;
+
+#[cfg(feature = "tokio-runtime")]
+use hyper::client::connect::Connect;
+#[cfg(feature = "tokio-runtime")]
+use hyper::client::HttpConnector;
+#[cfg(feature = "tokio-runtime")]
+use hyper::{Body, Client};
+#[cfg(feature = "tokio-runtime")]
+use hyper_tls::HttpsConnector;
+#[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+use isahc::{AsyncReadResponseExt, HttpClient};
+
+use crate::errors::*;
+
+/// Future returned by [`Transport::send`](trait.Transport.html#tymethod.send).
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<http::Response<Vec<u8>>>> + Send + 'a>>;
+
+/// HTTP stack used to deliver notices to the Honeybadger API.
+///
+/// The request passed to a transport is fully prepared: it holds the endpoint, the `X-API-Key`,
+/// `Accept` and `User-Agent` headers, and the notice serialized as JSON in its body. The
+/// transport returns the response, whose status and headers are used to decide whether the
+/// notice is retried, and whose body is carried by the returned error when the notice is
+/// rejected. Timeouts, retries and the circuit breaker are handled by the
+/// `Honeybadger` instance.
+///
+/// A transport that cannot reach its destination should return an `Error::Transport`, so that the
+/// notice is retried and spooled.
+///
+/// # Example
+///
+/// A transport appending notices to a file, instead of sending them over the network:
+///
+/// ```rust
+/// # use honeybadger::{ConfigBuilder, Honeybadger, Transport, TransportFuture};
+/// # use std::io::Write;
+/// struct FileTransport(std::path::PathBuf);
+///
+/// impl Transport for FileTransport {
+///     fn send(&self, request: http::Request<Vec<u8>>) -> TransportFuture<'_> {
+///         Box::pin(async move {
+///             let mut file = std::fs::OpenOptions::new()
+///                 .create(true)
+///                 .append(true)
+///                 .open(&self.0)?;
+///             file.write_all(request.body())?;
+///             file.write_all(b"\n")?;
+///             Ok(http::Response::builder().status(201).body(Vec::new())?)
+///         })
+///     }
+/// }
+///
+/// # let api_token = "ffffff";
+/// let config = ConfigBuilder::new(api_token).build();
+/// let transport = FileTransport("/tmp/honeybadger-notices.json".into());
+/// let honeybadger = Honeybadger::with_transport(config, transport).unwrap();
+/// ```
+pub trait Transport: Send + Sync {
+    /// Send a request holding a serialized notice, and return the response.
+    fn send(&self, request: http::Request<Vec<u8>>) -> TransportFuture<'_>;
+}
+
+/// Default transport, sending notices with a [Hyper](https://hyper.rs/) client.
+///
+/// Connections are pooled by the client, and shared by the clones of a `Honeybadger` instance.
+/// Requires the `tokio-runtime` feature.
+#[cfg(feature = "tokio-runtime")]
+pub struct HyperTransport<C = HttpsConnector<HttpConnector>> {
+    client: Client<C>,
+}
+
+#[cfg(feature = "tokio-runtime")]
+impl HyperTransport {
+    /// Constructs a transport using an HTTPS client.
+    pub fn new() -> Self {
+        HyperTransport {
+            client: Client::builder().build(HttpsConnector::new()),
+        }
+    }
+}
+
+#[cfg(feature = "tokio-runtime")]
+impl Default for HyperTransport {
+    fn default() -> Self {
+        HyperTransport::new()
+    }
+}
+
+#[cfg(feature = "tokio-runtime")]
+impl<C> HyperTransport<C> {
+    /// Constructs a transport using an existing Hyper client, for example to use a custom
+    /// connector or proxy.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The Hyper client used to send the notices
+    pub fn with_client(client: Client<C>) -> Self {
+        HyperTransport { client }
+    }
+}
+
+#[cfg(feature = "tokio-runtime")]
+impl<C> Transport for HyperTransport<C>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+{
+    fn send(&self, request: http::Request<Vec<u8>>) -> TransportFuture<'_> {
+        let response = self.client.request(request.map(Body::from));
+        Box::pin(async move {
+            let (parts, body) = response.await?.into_parts();
+            let body = hyper::body::to_bytes(body).await?;
+            Ok(http::Response::from_parts(parts, body.to_vec()))
+        })
+    }
+}
+
+/// Transport sending notices with an [Isahc](https://docs.rs/isahc) client, which drives its
+/// requests on a background thread and may be awaited on any runtime.
+///
+/// Connections are pooled by the client, and shared by the clones of a `Honeybadger` instance.
+/// Requires the `async-std-runtime` or `smol-runtime` feature.
+#[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+pub struct IsahcTransport {
+    client: HttpClient,
+}
+
+#[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+impl IsahcTransport {
+    /// Constructs a transport using a default client.
+    pub fn new() -> Result<Self> {
+        Ok(IsahcTransport {
+            client: HttpClient::new()?,
+        })
+    }
+
+    /// Constructs a transport using an existing Isahc client, for example to use a custom proxy
+    /// or TLS configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - The Isahc client used to send the notices
+    pub fn with_client(client: HttpClient) -> Self {
+        IsahcTransport { client }
+    }
+}
+
+#[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+impl Transport for IsahcTransport {
+    fn send(&self, request: http::Request<Vec<u8>>) -> TransportFuture<'_> {
+        Box::pin(async move {
+            let mut response = self.client.send_async(request).await?;
+            let body = response.bytes().await?;
+            Ok(response.map(|_| body))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    #[cfg(any(feature = "async-std-runtime", feature = "smol-runtime"))]
+    #[test]
+    fn test_isahc_transport() {
+        use crate::executor;
+        use crate::transport::*;
+        use mockito::mock;
+
+        let m = mock("POST", "/isahc/notices")
+            .match_header("x-api-key", "dummy-api-key")
+            .match_body("{}")
+            .with_status(201)
+            .with_body("{\"id\":\"42\"}")
+            .create();
+        let transport = IsahcTransport::new().unwrap();
+        let request = http::Request::post(format!("{}/isahc/notices", mockito::server_url()))
+            .header("X-API-Key", "dummy-api-key")
+            .body(b"{}".to_vec())
+            .unwrap();
+
+        let response = executor::block_on(transport.send(request))
+            .unwrap()
+            .unwrap();
+
+        m.assert();
+        assert_eq!(201, response.status());
+        assert_eq!(b"{\"id\":\"42\"}".to_vec(), *response.body());
+    }
+}